@@ -1,4 +1,5 @@
 use crate::config::{Config, SourceSet};
+use crate::resolve::{Classpath, load_classpath};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -40,18 +41,15 @@ fn copy_resources(source_set: &SourceSet, output_dir: &Path) {
     }
 }
 
-fn build_classpath(cache_dir: &str, main_output: Option<&str>, is_test: bool) -> String {
+fn build_classpath(main_output: Option<&str>, is_test: bool) -> String {
     let sep = if cfg!(windows) { ";" } else { ":" };
-    let mut cp_entries = Vec::new();
 
-    // Add dependency JARs
-    if let Ok(entries) = fs::read_dir(cache_dir) {
-        for entry in entries.filter_map(|e| e.ok()) {
-            if entry.path().extension().map_or(false, |ext| ext == "jar") {
-                cp_entries.push(entry.path().to_string_lossy().to_string());
-            }
-        }
-    }
+    // Add dependency JARs selected by the resolver
+    let mut cp_entries = load_classpath(if is_test {
+        Classpath::Test
+    } else {
+        Classpath::Compile
+    });
 
     // For test compilation, add main classes to classpath
     if is_test {
@@ -63,12 +61,7 @@ fn build_classpath(cache_dir: &str, main_output: Option<&str>, is_test: bool) ->
     cp_entries.join(sep)
 }
 
-fn compile_source_set(
-    source_set: &SourceSet,
-    cache_dir: &str,
-    is_test: bool,
-    main_output: Option<&str>,
-) -> bool {
+fn compile_source_set(source_set: &SourceSet, is_test: bool, main_output: Option<&str>) -> bool {
     // Get output directory
     let output_dir = source_set.output.as_deref().unwrap_or(if is_test {
         "build/classes/java/test"
@@ -163,7 +156,7 @@ fn compile_source_set(
     );
 
     // Build classpath
-    let classpath = build_classpath(cache_dir, main_output, is_test);
+    let classpath = build_classpath(main_output, is_test);
 
     // Compile Java files
     let mut cmd = Command::new("javac");
@@ -196,11 +189,9 @@ fn compile_source_set(
 }
 
 pub fn build_project(config: &Config) -> bool {
-    let cache_dir = ".rrrgradle/cache";
-
     // Compile main source set
     let main_success = if let Some(main) = &config.main {
-        compile_source_set(main, cache_dir, false, None)
+        compile_source_set(main, false, None)
     } else {
         true
     };
@@ -209,7 +200,7 @@ pub fn build_project(config: &Config) -> bool {
     if main_success {
         if let Some(test) = &config.test {
             let main_output = config.main.as_ref().and_then(|m| m.output.as_deref());
-            compile_source_set(test, cache_dir, true, main_output)
+            compile_source_set(test, true, main_output)
         } else {
             true
         }
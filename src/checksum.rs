@@ -0,0 +1,81 @@
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Checksum algorithms published as sidecar files next to repository artifacts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    /// Strongest first, so the best published sidecar is the one used
+    pub const PREFERENCE: [ChecksumAlgorithm; 3] = [
+        ChecksumAlgorithm::Sha512,
+        ChecksumAlgorithm::Sha256,
+        ChecksumAlgorithm::Sha1,
+    ];
+
+    /// Sidecar file extension, e.g. `sha256` for `foo-1.0.jar.sha256`
+    pub fn extension(self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Sha1 => "sha1",
+            ChecksumAlgorithm::Sha256 => "sha256",
+            ChecksumAlgorithm::Sha512 => "sha512",
+        }
+    }
+}
+
+/// Hex-encoded digest of a file's contents.
+pub fn file_digest(path: &Path, algorithm: ChecksumAlgorithm) -> io::Result<String> {
+    let bytes = fs::read(path)?;
+    Ok(match algorithm {
+        ChecksumAlgorithm::Sha1 => hex::encode(Sha1::digest(&bytes)),
+        ChecksumAlgorithm::Sha256 => hex::encode(Sha256::digest(&bytes)),
+        ChecksumAlgorithm::Sha512 => hex::encode(Sha512::digest(&bytes)),
+    })
+}
+
+/// Extract the digest from a sidecar file. Besides the bare hex digest, some repositories
+/// publish `sha1sum`-style `<digest>  <file name>` lines.
+pub fn parse_sidecar(content: &str) -> Option<String> {
+    let digest = content.split_whitespace().next()?.to_ascii_lowercase();
+    digest
+        .chars()
+        .all(|c| c.is_ascii_hexdigit())
+        .then_some(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sidecar_formats() {
+        let digest = "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12";
+        for content in [
+            digest.to_string(),
+            format!("{digest}\n"),
+            format!("{digest}  lib-1.0.jar"),
+            format!("{digest} *lib-1.0.jar\n"),
+            digest.to_uppercase(),
+        ] {
+            assert_eq!(
+                parse_sidecar(&content).as_deref(),
+                Some(digest),
+                "{content:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_sidecars_without_a_digest() {
+        for content in ["", "  \n", "<html>Not Found</html>", "sha1: 2fd4e1c6"] {
+            assert_eq!(parse_sidecar(content), None, "{content:?}");
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "rrrGradle")]
@@ -6,6 +6,14 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Never touch the network; use only cached and local artifacts
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Download every dependency again instead of trusting the cache
+    #[arg(long, global = true, conflicts_with = "offline")]
+    pub refresh_dependencies: bool,
 }
 
 #[derive(Subcommand)]
@@ -13,8 +21,18 @@ pub enum Commands {
     /// Initialize a new Rustapack project
     Init,
 
-    /// Fetch dependencies from Maven Central
-    Fetch,
+    /// Fetch dependencies from the configured repositories (Maven Central by default)
+    Fetch {
+        /// Fail if resolution would change rrrgradle.lock
+        #[arg(long)]
+        locked: bool,
+        /// Also download -sources JARs for IDE source attachment
+        #[arg(long)]
+        sources: bool,
+        /// Also download -javadoc JARs
+        #[arg(long)]
+        javadoc: bool,
+    },
 
     /// Build the Java project
     Build,
@@ -33,4 +51,78 @@ pub enum Commands {
         #[arg(long)]
         uber: bool,
     },
+
+    /// Package the JAR and install it with a generated POM into ~/.m2/repository
+    Install,
+
+    /// Add a dependency to rrrgradle.toml
+    Add {
+        /// group:artifact, optionally @version (defaults to the newest release)
+        dependency: String,
+        /// Add to [test_dependencies]
+        #[arg(long)]
+        test: bool,
+        /// Scope other than the table's default
+        #[arg(long, value_parser = ["compile", "runtime", "provided", "test"])]
+        scope: Option<String>,
+        /// Fetch dependencies afterwards
+        #[arg(long)]
+        fetch: bool,
+    },
+
+    /// Remove a dependency from rrrgradle.toml and prune rrrgradle.lock
+    Remove {
+        /// group:artifact
+        dependency: String,
+        /// Remove from [test_dependencies]
+        #[arg(long)]
+        test: bool,
+    },
+
+    /// Inspect the resolved dependency graph
+    Deps {
+        #[command(subcommand)]
+        command: DepsCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DepsCommand {
+    /// Print the resolved dependency tree of the main and test classpaths
+    Tree {
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Show every path from a direct dependency to an artifact
+    Why {
+        /// The artifact, as group:artifact
+        artifact: String,
+    },
+
+    /// List declared versions with newer patch, minor or major releases
+    Outdated {
+        /// Also consider alpha, beta, milestone, rc and SNAPSHOT versions
+        #[arg(long)]
+        prereleases: bool,
+    },
+
+    /// Raise declared versions in rrrgradle.toml to the newest release
+    Update {
+        /// Only move to newer patch releases
+        #[arg(long, conflicts_with = "minor")]
+        patch: bool,
+        /// Only move to newer minor or patch releases
+        #[arg(long)]
+        minor: bool,
+        /// Also consider alpha, beta, milestone, rc and SNAPSHOT versions
+        #[arg(long)]
+        prereleases: bool,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
 }
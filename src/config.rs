@@ -1,5 +1,6 @@
-use serde::Deserialize;
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Debug, Deserialize)]
 pub struct SourceSet {
@@ -9,10 +10,13 @@ pub struct SourceSet {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 pub struct Project {
+    pub group: Option<String>, // Maven groupId, needed by `install`
     pub name: String,
     pub version: String,
     pub main_class: String,
+    pub java_version: Option<u32>, // JVM the project targets; picks Gradle module variants
 
     // New fields for directory configuration
     pub source_dir: Option<String>,   // Source directory
@@ -25,8 +29,213 @@ pub struct Config {
     pub project: Project,
     pub main: Option<SourceSet>, // Main source set
     pub test: Option<SourceSet>, // Test source set
-    pub dependencies: Option<HashMap<String, String>>,
-    pub test_dependencies: Option<HashMap<String, String>>,
+    pub dependencies: Option<IndexMap<String, DependencySpec>>, // In declaration order
+    pub test_dependencies: Option<IndexMap<String, DependencySpec>>,
+    pub platforms: Option<IndexMap<String, String>>, // BOMs whose managed versions apply to all dependencies
+    pub fetch: Option<FetchSettings>,
+    pub repositories: Option<Vec<RepositoryConfig>>, // Looked up in order; Maven Central when absent
+}
+
+/// A `[[repositories]]` entry.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RepositoryConfig {
+    pub name: String,
+    pub url: String, // https://, http:// or file://
+    #[serde(default)]
+    pub groups: Vec<String>, // Only look up these groups here, e.g. ["com.example", "com.example.*"]
+}
+
+/// `[fetch]` table: how artifacts are downloaded and verified.
+#[derive(Debug, Default, Deserialize)]
+pub struct FetchSettings {
+    #[serde(default)]
+    pub checksums: ChecksumPolicy, // What to do when a `.sha1`/`.sha256`/`.sha512` is missing or wrong
+    #[serde(default)]
+    pub maven_local: bool, // Look in ~/.m2/repository before any remote repository
+    pub metadata_ttl: Option<String>, // How long cached maven-metadata.xml is trusted: "0", "30m", "24h", "7d"
+    pub retries: Option<u32>,         // Extra attempts after a transient download failure
+    pub connect_timeout: Option<String>, // Give up connecting to a repository after this long
+    pub read_timeout: Option<String>, // Give up on a download that sends nothing for this long
+    #[serde(default)]
+    pub pom_repositories: bool, // Also look in <repositories> declared by dependency POMs, for their own subtree
+    #[serde(default)]
+    pub pom_repository_hosts: Vec<String>, // Hosts those may be on, e.g. ["repository.jboss.org", "*.example.com"]
+}
+
+/// Parse a duration such as `"0"`, `"45s"`, `"30m"`, `"24h"` or `"7d"`, falling back to
+/// `default` with a warning naming the `[fetch]` key when it is unreadable.
+fn parse_duration(key: &str, value: Option<&str>, default: Duration) -> Duration {
+    let Some(value) = value else {
+        return default;
+    };
+    let (number, unit) = value.split_at(
+        value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len()),
+    );
+    let seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => 0,
+    };
+    match number.parse::<u64>() {
+        Ok(n) if seconds > 0 => Duration::from_secs(n * seconds),
+        _ => {
+            eprintln!("⚠️  Invalid {key} '{value}', using {}s", default.as_secs());
+            default
+        }
+    }
+}
+
+impl FetchSettings {
+    /// `metadata_ttl` as a duration; 24 hours when unset or unreadable.
+    pub fn metadata_ttl(&self) -> Duration {
+        parse_duration(
+            "metadata_ttl",
+            self.metadata_ttl.as_deref(),
+            Duration::from_secs(24 * 60 * 60),
+        )
+    }
+
+    /// `connect_timeout`; 10 seconds by default.
+    pub fn connect_timeout(&self) -> Duration {
+        parse_duration(
+            "connect_timeout",
+            self.connect_timeout.as_deref(),
+            Duration::from_secs(10),
+        )
+    }
+
+    /// `read_timeout`; 30 seconds by default.
+    pub fn read_timeout(&self) -> Duration {
+        parse_duration(
+            "read_timeout",
+            self.read_timeout.as_deref(),
+            Duration::from_secs(30),
+        )
+    }
+
+    /// `retries`; 3 by default.
+    pub fn retries(&self) -> u32 {
+        self.retries.unwrap_or(3)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumPolicy {
+    /// Reject the download
+    Fail,
+    /// Keep the download and print a warning
+    #[default]
+    Warn,
+    /// Keep the download silently
+    Ignore,
+}
+
+/// A dependency declaration: either a plain version string or an inline table,
+/// e.g. `"org.foo:bar" = { version = "1.2", exclude = ["commons-logging:*"] }`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum DependencySpec {
+    Version(String),
+    Detailed(DetailedDependency),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DetailedDependency {
+    pub version: Option<String>, // May be omitted when a platform manages it
+    #[serde(default)]
+    pub exclude: Vec<String>, // "group:artifact" patterns, `*` matches anything
+    pub classifier: Option<String>, // e.g. "linux-x86_64", "tests"
+    #[serde(rename = "type", alias = "extension")]
+    pub dep_type: Option<String>, // "jar" (default), "pom", "aar", "zip", ...
+    pub scope: Option<Scope>,    // Defaults to the table's scope
+}
+
+/// Maven dependency scopes; decides which classpaths an artifact lands on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    // Ordered from narrowest to widest so `max` picks the scope that wins when paths meet
+    Test,
+    #[serde(alias = "compileOnly")]
+    Provided,
+    Runtime,
+    Compile,
+}
+
+impl Scope {
+    /// Parse a POM `<scope>`; `None` for scopes that never contribute artifacts (`system`, `import`)
+    pub fn from_pom(scope: Option<&str>) -> Option<Scope> {
+        match scope.unwrap_or("compile") {
+            "compile" => Some(Scope::Compile),
+            "runtime" => Some(Scope::Runtime),
+            "provided" => Some(Scope::Provided),
+            "test" => Some(Scope::Test),
+            _ => None,
+        }
+    }
+
+    /// Scope of a transitive dependency declared with `child` scope under a `self`-scoped node,
+    /// per Maven's propagation table. `None` when the dependency is not transitive.
+    pub fn transitive(self, child: Scope) -> Option<Scope> {
+        match (self, child) {
+            (_, Scope::Provided | Scope::Test) => None,
+            (Scope::Compile, child) => Some(child),
+            (parent, _) => Some(parent),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Compile => "compile",
+            Scope::Runtime => "runtime",
+            Scope::Provided => "provided",
+            Scope::Test => "test",
+        }
+    }
+}
+
+impl DependencySpec {
+    /// Declared version, empty when it should come from a platform
+    pub fn version(&self) -> &str {
+        match self {
+            DependencySpec::Version(v) => v,
+            DependencySpec::Detailed(d) => d.version.as_deref().unwrap_or(""),
+        }
+    }
+
+    pub fn exclude(&self) -> &[String] {
+        match self {
+            DependencySpec::Version(_) => &[],
+            DependencySpec::Detailed(d) => &d.exclude,
+        }
+    }
+
+    pub fn classifier(&self) -> Option<&str> {
+        match self {
+            DependencySpec::Version(_) => None,
+            DependencySpec::Detailed(d) => d.classifier.as_deref(),
+        }
+    }
+
+    pub fn dep_type(&self) -> Option<&str> {
+        match self {
+            DependencySpec::Version(_) => None,
+            DependencySpec::Detailed(d) => d.dep_type.as_deref(),
+        }
+    }
+
+    /// Declared scope, or `default` (the scope implied by the table it appears in)
+    pub fn scope(&self, default: Scope) -> Scope {
+        match self {
+            DependencySpec::Version(_) => default,
+            DependencySpec::Detailed(d) => d.scope.unwrap_or(default),
+        }
+    }
 }
 
 impl Default for SourceSet {
@@ -62,3 +271,32 @@ pub fn load_config() -> Config {
 
     config
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transitive_scopes_follow_mavens_table() {
+        use Scope::{Compile, Provided, Runtime, Test};
+        // Rows: the node's scope; columns: the scope its POM declares a dependency with
+        let columns = [Compile, Provided, Runtime, Test];
+        for (parent, row) in [
+            (Compile, [Some(Compile), None, Some(Runtime), None]),
+            (Provided, [Some(Provided), None, Some(Provided), None]),
+            (Runtime, [Some(Runtime), None, Some(Runtime), None]),
+            (Test, [Some(Test), None, Some(Test), None]),
+        ] {
+            for (child, expected) in columns.into_iter().zip(row) {
+                assert_eq!(parent.transitive(child), expected, "{parent:?} → {child:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn pom_scopes_that_contribute_no_artifacts() {
+        assert_eq!(Scope::from_pom(None), Some(Scope::Compile));
+        assert_eq!(Scope::from_pom(Some("system")), None);
+        assert_eq!(Scope::from_pom(Some("import")), None);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::paths::rrrgradle_home;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// How requests to a repository authenticate.
+#[derive(Debug, Clone)]
+pub enum Auth {
+    Basic { username: String, password: String },
+    Bearer(String),
+}
+
+/// One table of `credentials.toml`, keyed by repository name.
+#[derive(Debug, Default, Deserialize)]
+struct CredentialEntry {
+    username: Option<String>,
+    password: Option<String>,
+    token: Option<String>,
+}
+
+impl CredentialEntry {
+    fn into_auth(self) -> Option<Auth> {
+        if let Some(token) = self.token {
+            return Some(Auth::Bearer(token));
+        }
+        Some(Auth::Basic {
+            username: self.username?,
+            password: self.password.unwrap_or_default(),
+        })
+    }
+}
+
+pub fn credentials_path() -> Option<PathBuf> {
+    rrrgradle_home().map(|home| home.join("credentials.toml"))
+}
+
+/// Prefix of the environment variables holding credentials for `repository`,
+/// e.g. `RRRGRADLE_REPO_MY_NEXUS` for a repository named `my-nexus`.
+pub fn env_prefix(repository: &str) -> String {
+    let name: String = repository
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("RRRGRADLE_REPO_{name}")
+}
+
+/// Credentials for a repository, kept out of the project file. Environment variables
+/// (`<prefix>_TOKEN`, or `<prefix>_USERNAME` and `<prefix>_PASSWORD`) take precedence
+/// over the repository's table in the user-level `credentials.toml`.
+pub struct Credentials {
+    file: HashMap<String, CredentialEntry>,
+}
+
+impl Credentials {
+    pub fn load() -> Self {
+        let file = credentials_path()
+            .and_then(|path| {
+                let content = fs::read_to_string(&path).ok()?;
+                match toml::from_str(&content) {
+                    Ok(entries) => Some(entries),
+                    Err(e) => {
+                        eprintln!("⚠️  Ignoring unreadable {}: {}", path.display(), e);
+                        None
+                    }
+                }
+            })
+            .unwrap_or_default();
+        Credentials { file }
+    }
+
+    pub fn for_repository(&mut self, repository: &str) -> Option<Auth> {
+        let prefix = env_prefix(repository);
+        let var = |suffix: &str| env::var(format!("{prefix}_{suffix}")).ok();
+        let from_env = CredentialEntry {
+            username: var("USERNAME"),
+            password: var("PASSWORD"),
+            token: var("TOKEN"),
+        };
+        from_env
+            .into_auth()
+            .or_else(|| self.file.remove(repository)?.into_auth())
+    }
+}
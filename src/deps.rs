@@ -0,0 +1,639 @@
+use crate::cli::OutputFormat;
+use crate::config::{Config, Scope};
+use crate::edit::{read_manifest, set_value, write_manifest};
+use crate::fetch::{FetchError, FetchOptions, open_cache, resolve_dependencies};
+use crate::metadata::is_dynamic;
+use crate::resolve::{DependencyEdge, EdgeOutcome, Resolution};
+use crate::version::{ComparableVersion, is_prerelease, is_range, release_numbers};
+use futures::future::join_all;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// One line of `deps tree`: a declaration and, when it was selected, the dependencies it
+/// brought in.
+#[derive(Debug, Serialize)]
+struct TreeNode {
+    /// `group:artifact[:classifier]`
+    dependency: String,
+    requested: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    selected: Option<String>,
+    scope: &'static str,
+    /// `selected`, `duplicate`, `conflict` or `excluded`
+    outcome: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    excluded_by: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<TreeNode>,
+}
+
+#[derive(Debug, Serialize)]
+struct DependencyTree {
+    main: Vec<TreeNode>,
+    test: Vec<TreeNode>,
+}
+
+/// The resolver's edges indexed by declaring artifact.
+struct Graph<'a> {
+    /// Distinct declarations in breadth-first order
+    edges: Vec<&'a DependencyEdge>,
+    children: HashMap<Option<&'a str>, Vec<&'a DependencyEdge>>,
+    /// Final scope of every selected artifact, after widening
+    scopes: HashMap<String, Scope>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(resolution: &'a Resolution) -> Self {
+        let mut seen = HashSet::new();
+        // A node walked again after its scope widened declares the same edges twice. Direct
+        // declarations keep their scope in the key, so one in both tables shows up in both
+        let edges: Vec<_> = resolution
+            .edges
+            .iter()
+            .filter(|edge| {
+                let table = edge.parent.is_none().then_some(edge.scope == Scope::Test);
+                seen.insert((edge.parent.as_deref(), edge.key.as_str(), table))
+            })
+            .collect();
+        let mut children: HashMap<_, Vec<_>> = HashMap::new();
+        for edge in &edges {
+            children
+                .entry(edge.parent.as_deref())
+                .or_default()
+                .push(*edge);
+        }
+        let scopes = resolution
+            .artifacts
+            .iter()
+            .map(|a| (a.key(), a.scope))
+            .collect();
+        Graph {
+            edges,
+            children,
+            scopes,
+        }
+    }
+
+    fn node(&self, edge: &'a DependencyEdge, visiting: &mut HashSet<&'a str>) -> TreeNode {
+        let expand = edge.outcome == EdgeOutcome::Selected && visiting.insert(&edge.key);
+        let mut children = Vec::new();
+        if expand {
+            for child in self
+                .children
+                .get(&Some(edge.key.as_str()))
+                .into_iter()
+                .flatten()
+            {
+                children.push(self.node(child, visiting));
+            }
+            visiting.remove(edge.key.as_str());
+        }
+        let scope = match edge.outcome {
+            EdgeOutcome::Selected => self.scopes.get(&edge.key).copied().unwrap_or(edge.scope),
+            _ => edge.scope,
+        };
+        TreeNode {
+            dependency: dependency(edge),
+            requested: edge.requested.clone(),
+            selected: edge.selected.clone(),
+            scope: scope.as_str(),
+            outcome: match edge.outcome {
+                EdgeOutcome::Selected => "selected",
+                EdgeOutcome::Duplicate => "duplicate",
+                EdgeOutcome::Conflict => "conflict",
+                EdgeOutcome::Excluded => "excluded",
+            },
+            excluded_by: edge.excluded_by.clone(),
+            children,
+        }
+    }
+
+    /// Trees of the direct dependencies declared for one classpath
+    fn roots(&self, test: bool) -> Vec<TreeNode> {
+        self.children
+            .get(&None)
+            .into_iter()
+            .flatten()
+            .filter(|edge| (edge.scope == Scope::Test) == test)
+            .map(|edge| self.node(edge, &mut HashSet::new()))
+            .collect()
+    }
+
+    /// Every chain of declarations from a direct dependency down to `edge`, each listed
+    /// root first. Follows duplicates too: each one is a path that asked for the artifact.
+    fn chains(&self, edge: &'a DependencyEdge) -> Vec<Vec<&'a DependencyEdge>> {
+        let mut chains = Vec::new();
+        self.walk_up(vec![edge], &mut chains);
+        for chain in &mut chains {
+            chain.reverse();
+        }
+        chains
+    }
+
+    fn walk_up(&self, chain: Vec<&'a DependencyEdge>, chains: &mut Vec<Vec<&'a DependencyEdge>>) {
+        let Some(parent) = chain.last().and_then(|edge| edge.parent.as_deref()) else {
+            chains.push(chain);
+            return;
+        };
+        let declarations = self.edges.iter().filter(|edge| {
+            edge.key == parent
+                && matches!(edge.outcome, EdgeOutcome::Selected | EdgeOutcome::Duplicate)
+                && !chain.iter().any(|seen| seen.key == edge.key)
+        });
+        for declaration in declarations {
+            let mut longer = chain.clone();
+            longer.push(declaration);
+            self.walk_up(longer, chains);
+        }
+    }
+}
+
+/// `group:artifact[:classifier]`
+fn dependency(edge: &DependencyEdge) -> String {
+    match &edge.classifier {
+        Some(classifier) => format!("{}:{}", edge.dep, classifier),
+        None => edge.dep.clone(),
+    }
+}
+
+/// `group:artifact:version` as Gradle prints it, with `requested -> selected` when
+/// mediation or a range picked another version.
+fn version_label(dependency: &str, requested: &str, selected: Option<&str>) -> String {
+    match selected {
+        Some(selected) if requested.is_empty() => format!("{dependency} -> {selected}"),
+        Some(selected) if selected != requested => {
+            format!("{dependency}:{requested} -> {selected}")
+        }
+        _ => format!("{dependency}:{requested}"),
+    }
+}
+
+fn label(node: &TreeNode) -> String {
+    version_label(&node.dependency, &node.requested, node.selected.as_deref())
+}
+
+fn print_nodes(nodes: &[TreeNode], prefix: &str, default_scope: &str) {
+    for (i, node) in nodes.iter().enumerate() {
+        let last = i + 1 == nodes.len();
+        let mut line = format!(
+            "{prefix}{}{}",
+            if last { "└── " } else { "├── " },
+            label(node)
+        );
+        if node.scope != default_scope {
+            line.push_str(&format!(" ({})", node.scope));
+        }
+        match node.outcome {
+            "duplicate" => line.push_str(" (*)"),
+            "excluded" => line.push_str(&format!(
+                " (excluded by {})",
+                node.excluded_by.as_deref().unwrap_or_default()
+            )),
+            _ => {}
+        }
+        println!("{line}");
+        let child_prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+        print_nodes(&node.children, &child_prefix, default_scope);
+    }
+}
+
+fn print_tree(tree: &DependencyTree) {
+    for (name, nodes, default_scope) in [
+        ("main", &tree.main, Scope::Compile),
+        ("test", &tree.test, Scope::Test),
+    ] {
+        println!("{name}");
+        if nodes.is_empty() {
+            println!("└── (no dependencies)");
+        }
+        print_nodes(nodes, "", default_scope.as_str());
+        println!();
+    }
+    println!("(*) already listed above; its dependencies are shown there");
+}
+
+/// Resolve the project's dependencies and print the graph of each classpath.
+pub async fn tree(
+    config: &Config,
+    options: &FetchOptions,
+    format: OutputFormat,
+) -> Result<(), FetchError> {
+    let resolution = resolve_dependencies(config, options).await?;
+    let graph = Graph::new(&resolution);
+    let tree = DependencyTree {
+        main: graph.roots(false),
+        test: graph.roots(true),
+    };
+    match format {
+        OutputFormat::Text => print_tree(&tree),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&tree).map_err(std::io::Error::other)?
+        ),
+    }
+    Ok(())
+}
+
+/// Resolve the project's dependencies and print every chain from a direct dependency to
+/// `query` (`group:artifact`), with the version each chain requested and the one selected.
+pub async fn why(config: &Config, options: &FetchOptions, query: &str) -> Result<(), FetchError> {
+    let resolution = resolve_dependencies(config, options).await?;
+    let graph = Graph::new(&resolution);
+
+    let mut targets: Vec<_> = graph
+        .edges
+        .iter()
+        .filter(|edge| edge.dep == query)
+        .copied()
+        .collect();
+    if targets.is_empty() {
+        println!("{query} is not in the dependency graph.");
+        return Ok(());
+    }
+    // Declarations that won first, then the ones mediated away
+    targets.sort_by_key(|edge| edge.outcome != EdgeOutcome::Selected);
+
+    for artifact in resolution.artifacts.iter().filter(|a| a.dep == query) {
+        println!("{} ({})", artifact.coordinate(), artifact.scope.as_str());
+    }
+    if !resolution.artifacts.iter().any(|a| a.dep == query) {
+        println!("{query} is not on any classpath");
+    }
+    println!();
+
+    for target in targets {
+        for chain in graph.chains(target) {
+            let mut line = chain
+                .iter()
+                .map(|edge| {
+                    let label =
+                        version_label(&dependency(edge), &edge.requested, edge.selected.as_deref());
+                    if edge.parent.is_none() && edge.scope == Scope::Test {
+                        format!("{label} (test)")
+                    } else {
+                        label
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" → ");
+            match target.outcome {
+                EdgeOutcome::Selected => line.push_str(" (selected)"),
+                EdgeOutcome::Duplicate => {}
+                EdgeOutcome::Conflict => line.push_str(" (conflict)"),
+                EdgeOutcome::Excluded => line.push_str(&format!(
+                    " (excluded by {})",
+                    target.excluded_by.as_deref().unwrap_or_default()
+                )),
+            }
+            println!("{line}");
+        }
+    }
+    Ok(())
+}
+
+/// How far `deps update` may move a version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpgradeLevel {
+    /// Same major and minor version
+    Patch,
+    /// Same major version
+    Minor,
+    Major,
+}
+
+/// Newer versions of one version declared in `rrrgradle.toml`.
+struct Outdated {
+    /// `dependencies`, `test_dependencies` or `platforms`
+    table: &'static str,
+    dep: String,
+    current: String,
+    patch: Option<String>,
+    minor: Option<String>,
+    major: Option<String>,
+}
+
+impl Outdated {
+    fn name(&self) -> String {
+        match self.table {
+            "test_dependencies" => format!("{} (test)", self.dep),
+            "platforms" => format!("{} (platform)", self.dep),
+            _ => self.dep.clone(),
+        }
+    }
+
+    fn target(&self, level: UpgradeLevel) -> Option<&String> {
+        match level {
+            UpgradeLevel::Patch => self.patch.as_ref(),
+            UpgradeLevel::Minor => self.minor.as_ref(),
+            UpgradeLevel::Major => self.major.as_ref(),
+        }
+    }
+}
+
+/// Versions declared in `rrrgradle.toml` that name one release, by table and then
+/// name. Ranges, `LATEST`/`RELEASE` and versions left to a platform are skipped.
+fn declared_versions(config: &Config) -> Vec<(&'static str, String, String)> {
+    let mut declared = Vec::new();
+    for (table, deps) in [
+        ("dependencies", config.dependencies.as_ref()),
+        ("test_dependencies", config.test_dependencies.as_ref()),
+    ] {
+        for (dep, spec) in deps.into_iter().flatten() {
+            declared.push((table, dep.clone(), spec.version().to_string()));
+        }
+    }
+    for (dep, version) in config.platforms.iter().flatten() {
+        declared.push(("platforms", dep.clone(), version.clone()));
+    }
+    declared.retain(|(_, _, version)| {
+        !version.is_empty() && !is_range(version) && !is_dynamic(version)
+    });
+    declared.sort();
+    declared
+}
+
+/// Text after the last `-` when it is a platform qualifier rather than a number or a
+/// pre-release, e.g. `jre` in `31.1-jre`; upgrades keep it so Guava stays on the `-jre` line.
+fn flavor(version: &str) -> Option<&str> {
+    let version = version.trim_end_matches("-SNAPSHOT");
+    let (_, suffix) = version.rsplit_once('-')?;
+    (suffix.chars().all(|c| c.is_ascii_alphabetic()) && !is_prerelease(version)).then_some(suffix)
+}
+
+/// The newest of `versions` above `current` in each upgrade band.
+fn upgrades(current: &str, versions: &[String], prereleases: bool) -> [Option<String>; 3] {
+    let numbers = |v: &str| -> Vec<u64> {
+        release_numbers(v)
+            .split('.')
+            .map(|n| n.parse().unwrap_or(0))
+            .collect()
+    };
+    let current_numbers = numbers(current);
+    let current_version = ComparableVersion::new(current);
+    let newer: Vec<&String> = versions
+        .iter()
+        .filter(|v| prereleases || !is_prerelease(v))
+        .filter(|v| flavor(v) == flavor(current))
+        .filter(|v| ComparableVersion::new(v) > current_version)
+        .collect();
+    // Newest candidate agreeing with `current` on its first `same` numbers
+    let newest = |same: usize| {
+        newer
+            .iter()
+            .filter(|v| {
+                let candidate = numbers(v);
+                (0..same)
+                    .all(|i| candidate.get(i).unwrap_or(&0) == current_numbers.get(i).unwrap_or(&0))
+            })
+            .max_by_key(|v| ComparableVersion::new(v))
+            .map(|v| v.to_string())
+    };
+    [newest(2), newest(1), newest(0)]
+}
+
+/// Look up every declared version in `maven-metadata.xml` and keep the ones with newer
+/// releases, in declaration-table order.
+async fn find_outdated(
+    config: &Config,
+    options: &FetchOptions,
+    prereleases: bool,
+) -> Result<Vec<Outdated>, FetchError> {
+    let (fetcher, _lock) = open_cache(config, options)?;
+    let lookups = declared_versions(config)
+        .into_iter()
+        .map(|(table, dep, current)| {
+            let fetcher = &fetcher;
+            async move {
+                let (group_id, artifact_id) = dep.split_once(':')?;
+                let artifact_dir = format!("{}/{}", group_id.replace('.', "/"), artifact_id);
+                let metadata = fetcher.fetch_metadata(group_id, &artifact_dir, &[]).await;
+                let Some(metadata) = metadata else {
+                    eprintln!("⚠️  No maven-metadata.xml lists versions of {dep}");
+                    return None;
+                };
+                let [patch, minor, major] = upgrades(&current, &metadata.versions, prereleases);
+                major.is_some().then_some(Outdated {
+                    table,
+                    dep,
+                    current,
+                    patch,
+                    minor,
+                    major,
+                })
+            }
+        });
+    Ok(join_all(lookups).await.into_iter().flatten().collect())
+}
+
+/// Print the declared versions that have newer releases.
+pub async fn outdated(
+    config: &Config,
+    options: &FetchOptions,
+    prereleases: bool,
+) -> Result<(), FetchError> {
+    let outdated = find_outdated(config, options, prereleases).await?;
+    if outdated.is_empty() {
+        println!("✓ All dependencies are up to date.");
+        return Ok(());
+    }
+
+    let header = ["Dependency", "Current", "Patch", "Minor", "Major"];
+    let rows: Vec<[String; 5]> = outdated
+        .iter()
+        .map(|o| {
+            let or_dash = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+            [
+                o.name(),
+                o.current.clone(),
+                or_dash(&o.patch),
+                or_dash(&o.minor),
+                or_dash(&o.major),
+            ]
+        })
+        .collect();
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([header[i].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let print_row = |cells: &[&str]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    };
+    print_row(&header);
+    for row in &rows {
+        print_row(&row.each_ref().map(String::as_str));
+    }
+    Ok(())
+}
+
+/// Raise declared versions in `rrrgradle.toml` to the newest release allowed by `level`,
+/// editing the file in place so comments and formatting survive.
+pub async fn update(
+    config: &Config,
+    options: &FetchOptions,
+    level: UpgradeLevel,
+    prereleases: bool,
+) -> Result<(), FetchError> {
+    let outdated = find_outdated(config, options, prereleases).await?;
+    let mut document = read_manifest()?;
+
+    let mut updated = 0;
+    for entry in &outdated {
+        let Some(target) = entry.target(level) else {
+            continue;
+        };
+        let Some(item) = document
+            .get_mut(entry.table)
+            .and_then(|table| table.get_mut(&entry.dep))
+        else {
+            continue;
+        };
+        // `"g:a" = "1.0"`, or the `version` key of an inline or standard table
+        let version = if item.is_str() {
+            Some(item)
+        } else {
+            item.get_mut("version")
+        };
+        let Some(value) = version.and_then(|v| v.as_value_mut()) else {
+            continue;
+        };
+        set_value(value, target.as_str());
+        println!("↑ {} {} → {}", entry.name(), entry.current, target);
+        updated += 1;
+    }
+
+    if updated == 0 {
+        println!("✓ Nothing to update.");
+        return Ok(());
+    }
+    write_manifest(&document)?;
+    println!(
+        "✓ Updated {updated} version(s) in rrrgradle.toml; run `rrrGradle fetch` to apply them."
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, TempDir, repository_url};
+    use crate::resolve::resolve_graph;
+
+    fn versions(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn flavor_is_a_trailing_platform_qualifier() {
+        for (version, expected) in [
+            ("31.1-jre", Some("jre")),
+            ("31.1-android", Some("android")),
+            ("32.0-jre-SNAPSHOT", Some("jre")),
+            ("1.0", None),
+            ("1.0-SNAPSHOT", None),
+            ("1.0-alpha", None),
+            ("1.0-rc1", None),
+            ("1.0-2", None),
+            ("2.3.RELEASE", None),
+        ] {
+            assert_eq!(flavor(version), expected, "{version}");
+        }
+    }
+
+    #[test]
+    fn upgrades_are_banded_by_patch_minor_and_major() {
+        let available = versions(&[
+            "1.2.3",
+            "1.2.4",
+            "1.2.10",
+            "1.3.0",
+            "1.9.1",
+            "2.0.0",
+            "3.1",
+            "1.2.11-rc1",
+            "2.1.0-beta",
+            "1.2.2",
+        ]);
+        for (current, prereleases, expected) in [
+            ("1.2.3", false, [Some("1.2.10"), Some("1.9.1"), Some("3.1")]),
+            (
+                "1.2.3",
+                true,
+                [Some("1.2.11-rc1"), Some("1.9.1"), Some("3.1")],
+            ),
+            ("1.9.1", false, [None, None, Some("3.1")]),
+            ("2.0.0", true, [None, Some("2.1.0-beta"), Some("3.1")]),
+            ("3.1", false, [None, None, None]),
+            // Missing numbers count as zero: 1.3 is the same release as 1.3.0
+            ("1.3", false, [None, Some("1.9.1"), Some("3.1")]),
+        ] {
+            assert_eq!(
+                upgrades(current, &available, prereleases),
+                expected.map(|v| v.map(str::to_string)),
+                "{current} {prereleases}"
+            );
+        }
+    }
+
+    #[test]
+    fn upgrades_stay_on_the_current_flavor() {
+        let available = versions(&["31.1-jre", "31.1-android", "32.1.2-jre", "32.1.2-android"]);
+        assert_eq!(
+            upgrades("31.0-jre", &available, false),
+            [None, Some("31.1-jre"), Some("32.1.2-jre")].map(|v| v.map(str::to_string))
+        );
+        assert_eq!(upgrades("32.1.2", &available, false), [None, None, None]);
+    }
+
+    #[tokio::test]
+    async fn declaration_in_both_tables_is_a_root_of_both_trees() {
+        let config = fixtures::config(&format!(
+            r#"
+            [dependencies]
+            "com.example:app" = "1.0"
+
+            [test_dependencies]
+            "com.example:app" = "1.0"
+
+            [fetch]
+            checksums = "ignore"
+
+            [[repositories]]
+            name = "graph"
+            url = "{}"
+            "#,
+            repository_url("graph")
+        ));
+        let cache = TempDir::new("deps-both-tables");
+        let resolution = resolve_graph(&config, &fixtures::fetcher(&config, &cache))
+            .await
+            .unwrap();
+        let graph = Graph::new(&resolution);
+
+        let roots = |test| -> Vec<String> {
+            graph
+                .roots(test)
+                .into_iter()
+                .map(|node| node.dependency)
+                .collect()
+        };
+        assert_eq!(roots(false), ["com.example:app"]);
+        assert_eq!(roots(true), ["com.example:app"]);
+
+        let util = graph
+            .edges
+            .iter()
+            .find(|edge| edge.dep == "com.example:util")
+            .unwrap();
+        assert_eq!(graph.chains(util).len(), 2);
+    }
+}
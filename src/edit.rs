@@ -0,0 +1,230 @@
+use crate::config::{Config, Scope, load_config};
+use crate::fetch::{FetchError, FetchOptions, dep_to_path, open_cache, resolve_dependencies};
+use crate::lockfile::{LockFile, read_lock_file, write_lock_file};
+use crate::paths::cache_root;
+use crate::resolve::{read_resolved_classpath, write_resolved_classpath};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use thiserror::Error;
+use toml_edit::{DocumentMut, InlineTable, Item, Value};
+
+const MANIFEST: &str = "rrrgradle.toml";
+
+#[derive(Debug, Error)]
+pub enum EditError {
+    #[error("expected group:artifact[@version], got `{0}`")]
+    InvalidCoordinate(String),
+    #[error("no release of {0} is listed in maven-metadata.xml; pass one with {0}@<version>")]
+    NoRelease(String),
+    #[error("{0} is not declared in [{1}]")]
+    NotDeclared(String, &'static str),
+    #[error("[test_dependencies] entries are always test-scoped; drop --scope {0} or --test")]
+    ScopeInTestTable(&'static str),
+    #[error("{0}")]
+    Fetch(#[from] FetchError),
+    #[error("{0}")]
+    Io(#[from] io::Error),
+}
+
+/// `rrrgradle.toml` as an editable document that keeps comments and formatting.
+pub fn read_manifest() -> io::Result<DocumentMut> {
+    fs::read_to_string(MANIFEST)?
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn write_manifest(document: &DocumentMut) -> io::Result<()> {
+    fs::write(MANIFEST, document.to_string())
+}
+
+/// Replace a value, keeping the whitespace and comment around it.
+pub fn set_value(value: &mut Value, new: impl Into<Value>) {
+    let decor = value.decor().clone();
+    *value = new.into();
+    *value.decor_mut() = decor;
+}
+
+fn table_name(test: bool) -> &'static str {
+    if test {
+        "test_dependencies"
+    } else {
+        "dependencies"
+    }
+}
+
+/// Split `group:artifact[@version]`.
+fn parse_coordinate(spec: &str) -> Result<(String, Option<String>), EditError> {
+    let (dep, version) = match spec.split_once('@') {
+        Some((dep, version)) => (dep, Some(version.to_string())),
+        None => (spec, None),
+    };
+    match dep.split_once(':') {
+        Some((group, artifact))
+            if !group.is_empty() && !artifact.is_empty() && !artifact.contains(':') =>
+        {
+            Ok((dep.to_string(), version.filter(|v| !v.is_empty())))
+        }
+        _ => Err(EditError::InvalidCoordinate(spec.to_string())),
+    }
+}
+
+async fn newest_release(
+    config: &Config,
+    options: &FetchOptions,
+    dep: &str,
+) -> Result<String, EditError> {
+    let (fetcher, _lock) = open_cache(config, options)?;
+    let (group_id, artifact_id) = dep.split_once(':').unwrap_or_default();
+    let artifact_dir = format!("{}/{}", group_id.replace('.', "/"), artifact_id);
+    fetcher
+        .fetch_metadata(group_id, &artifact_dir, &[])
+        .await
+        .and_then(|metadata| metadata.newest_release())
+        .ok_or_else(|| EditError::NoRelease(dep.to_string()))
+}
+
+/// Declare `spec` (`group:artifact[@version]`) in `[dependencies]`, or
+/// `[test_dependencies]` when `test`, using the newest release when no version is given.
+/// An existing declaration keeps its other keys and only has its version (and scope) set.
+pub async fn add_dependency(
+    config: &Config,
+    options: &FetchOptions,
+    spec: &str,
+    test: bool,
+    scope: Option<Scope>,
+) -> Result<(), EditError> {
+    let (dep, version) = parse_coordinate(spec)?;
+    let version = match version {
+        Some(version) => version,
+        None => newest_release(config, options, &dep).await?,
+    };
+    let table = table_name(test);
+    // The table's own scope is implied, so only other scopes need the inline-table form
+    let scope = scope.filter(|s| *s != if test { Scope::Test } else { Scope::Compile });
+    if let Some(scope) = scope.filter(|_| test) {
+        return Err(EditError::ScopeInTestTable(scope.as_str()));
+    }
+
+    let mut document = read_manifest()?;
+    if !document.contains_key(table) {
+        // Keep a new table next to the other dependency table rather than after everything
+        let mut new_table = toml_edit::Table::new();
+        if let Some(position) = ["dependencies", "test_dependencies"]
+            .iter()
+            .filter_map(|name| document.get(name)?.as_table()?.position())
+            .max()
+        {
+            new_table.set_position(position);
+        }
+        document.insert(table, Item::Table(new_table));
+    }
+    let deps = document[table].as_table_like_mut().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("[{table}] is not a table"),
+        )
+    })?;
+
+    let existed = deps.contains_key(&dep);
+    match deps.get_mut(&dep) {
+        Some(item) if item.is_table_like() => {
+            item["version"] = toml_edit::value(version.as_str());
+            if let Some(scope) = scope {
+                item["scope"] = toml_edit::value(scope.as_str());
+            }
+        }
+        Some(Item::Value(value)) if scope.is_none() => set_value(value, version.as_str()),
+        _ => {
+            let item = match scope {
+                Some(scope) => {
+                    let mut detailed = InlineTable::new();
+                    detailed.insert("version", version.as_str().into());
+                    detailed.insert("scope", scope.as_str().into());
+                    toml_edit::value(detailed)
+                }
+                None => toml_edit::value(version.as_str()),
+            };
+            deps.insert(&dep, item);
+        }
+    }
+    write_manifest(&document)?;
+
+    println!(
+        "✓ {} {dep}@{version} in [{table}]",
+        if existed { "Updated" } else { "Added" }
+    );
+    Ok(())
+}
+
+/// Remove `dep` from `[dependencies]`, or `[test_dependencies]` when `test`, then drop
+/// the artifacts nothing depends on any more from `rrrgradle.lock` and the classpath.
+pub async fn remove_dependency(
+    options: &FetchOptions,
+    dep: &str,
+    test: bool,
+) -> Result<(), EditError> {
+    let table = table_name(test);
+    let mut document = read_manifest()?;
+    let removed = document
+        .get_mut(table)
+        .and_then(Item::as_table_like_mut)
+        .and_then(|deps| deps.remove(dep));
+    if removed.is_none() {
+        return Err(EditError::NotDeclared(dep.to_string(), table));
+    }
+    write_manifest(&document)?;
+    println!("✓ Removed {dep} from [{table}]");
+
+    prune(options).await
+}
+
+/// Re-resolve the edited project and keep only the lockfile entries and classpath JARs
+/// that are still part of the graph.
+async fn prune(options: &FetchOptions) -> Result<(), EditError> {
+    let config = load_config();
+    let resolution = resolve_dependencies(&config, options).await?;
+
+    if let Some(lock) = read_lock_file() {
+        let resolved: HashSet<(String, String)> = resolution
+            .artifacts
+            .iter()
+            .map(|a| (a.coordinate(), a.extension.clone()))
+            .collect();
+        let (kept, pruned): (Vec<_>, Vec<_>) = lock
+            .artifacts
+            .into_iter()
+            .partition(|a| resolved.contains(&(a.id.clone(), a.dep_type.clone())));
+        for artifact in &pruned {
+            println!("- {}", artifact.id);
+        }
+        if !pruned.is_empty() {
+            write_lock_file(&LockFile::new(kept))?;
+        }
+    }
+
+    if let Some(mut classpath) = read_resolved_classpath() {
+        let cache_dir = cache_root();
+        let jars: HashSet<String> = resolution
+            .artifacts
+            .iter()
+            .filter_map(|a| {
+                let (base_path, jar_name, _) =
+                    dep_to_path(&a.dep, &a.version, a.classifier.as_deref(), &a.extension)?;
+                Some(
+                    cache_dir
+                        .join(base_path)
+                        .join(jar_name)
+                        .to_string_lossy()
+                        .to_string(),
+                )
+            })
+            .collect();
+        let before = classpath.entries.len();
+        classpath.entries.retain(|entry| jars.contains(&entry.path));
+        if classpath.entries.len() != before {
+            write_resolved_classpath(&classpath);
+        }
+    }
+    Ok(())
+}
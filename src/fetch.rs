@@ -1,13 +1,63 @@
-use crate::config::Config;
-use crate::pom::parse_pom_model;
+use crate::checksum::{ChecksumAlgorithm, file_digest, parse_sidecar};
+use crate::config::{ChecksumPolicy, Config, FetchSettings, Scope};
+use crate::credentials::{credentials_path, env_prefix};
+use crate::lockfile::{LockFile, LockedArtifact, read_lock_file, write_lock_file};
+use crate::metadata::{Metadata, base_version, parse_metadata};
+use crate::paths::cache_root;
+use crate::pom::PomRepository;
+use crate::repository::{
+    Repository, configured_repositories, https_host, local_path, maven_local_repository,
+};
+use crate::resolve::{
+    ClasspathEntry, ModelCache, Resolution, ResolveError, ResolvedArtifact, ResolvedClasspath,
+    resolve_graph, write_resolved_classpath,
+};
+use crate::version::ComparableVersion;
+use futures::FutureExt;
 use futures::stream::{FuturesUnordered, StreamExt};
-use num_cpus;
-use std::collections::{HashSet, VecDeque};
-use std::fs;
+use glob::Pattern;
+use reqwest::StatusCode;
+use reqwest::header::RANGE;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs::{self, TryLockError};
+use std::io;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
 use tokio::fs as async_fs;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::Semaphore;
+
+#[derive(Debug, Default)]
+pub struct FetchOptions {
+    /// Fail instead of updating `rrrgradle.lock` when resolution changes it
+    pub locked: bool,
+    /// Resolve from the cache and local repositories only, never the network
+    pub offline: bool,
+    /// Download every artifact again even if it is already cached
+    pub refresh: bool,
+    /// Also download each artifact's `-sources` JAR, where one is published
+    pub sources: bool,
+    /// Also download each artifact's `-javadoc` JAR, where one is published
+    pub javadoc: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum FetchError {
+    #[error("rrrgradle.lock does not exist; run `rrrGradle fetch` without --locked to create it")]
+    MissingLockFile,
+    #[error("resolution would change rrrgradle.lock:\n{}", .0.join("\n"))]
+    OutdatedLockFile(Vec<String>),
+    #[error("--offline: not in the cache or a local repository:\n{}", .0.join("\n"))]
+    MissingOffline(Vec<String>),
+    #[error("{} file(s) could not be downloaded:\n{}", .0.len(), .0.join("\n"))]
+    Failed(Vec<String>),
+    #[error("{0}")]
+    Resolve(#[from] ResolveError),
+    #[error("{0}")]
+    Io(#[from] io::Error),
+}
 
 // Function to get the optimal number of concurrent downloads
 // Since downloading is I/O bound, we can use more threads than CPU cores
@@ -15,149 +65,689 @@ fn get_max_concurrent_downloads() -> usize {
     num_cpus::get() * 4
 }
 
-/// Download file from `url` and save to `path`, unless it already exists.
-async fn fetch_file_async(url: &str, path: &Path, is_test: bool) {
-    if path.exists() {
-        eprintln!(
-            "✔️  Cached: {} ({})",
-            path.display(),
-            if is_test { "test" } else { "main" }
-        );
-        return;
+/// Outcome of trying one repository, or every repository in turn, for a file.
+enum Download {
+    Done,
+    /// The repository does not have the file; try the next one
+    NotFound,
+    /// Worth another attempt: a connection error, a timeout, a 5xx or 429 response, or a
+    /// body cut off before its end
+    Retry,
+    Failed,
+}
+
+/// What checksum verification decided about a downloaded file.
+#[derive(Debug, PartialEq, Eq)]
+enum Checked {
+    Keep,
+    /// The checksum policy rejects it
+    Reject,
+    /// A resumed download that does not match; start again from the first byte
+    Restart,
+}
+
+/// Shared download state: where artifacts are cached, which repositories they come from,
+/// how many downloads may run at once, and how downloads are verified.
+pub struct Fetcher {
+    pub cache_dir: PathBuf,
+    repositories: Vec<Repository>,
+    client: reqwest::Client,
+    /// `~/.m2/repository`, when `[fetch] maven_local` is enabled
+    maven_local: Option<PathBuf>,
+    pool: Semaphore,
+    checksum_policy: ChecksumPolicy,
+    metadata_ttl: Duration,
+    offline: bool,
+    /// Set by `--refresh-dependencies`; `refreshed` keeps each file to one download per run
+    refresh: bool,
+    refreshed: Mutex<HashSet<PathBuf>>,
+    /// Files that `--offline` could not find locally
+    missing: Mutex<BTreeSet<String>>,
+    /// Extra attempts after a transient failure, with exponential backoff
+    retries: u32,
+    /// Files that could not be downloaded even after retrying
+    failed: Mutex<BTreeSet<String>>,
+    /// `[fetch] pom_repositories`: repositories declared in dependency POMs may be used
+    pom_repositories: bool,
+    /// `[fetch] pom_repository_hosts`: the hosts those repositories may be on
+    pom_repository_hosts: Vec<Pattern>,
+    /// POM-declared repository URLs already reported as unused, so each is reported once
+    ignored_repositories: Mutex<HashSet<String>>,
+    /// Models already built during this run
+    pub models: ModelCache,
+}
+
+impl Fetcher {
+    pub fn new(config: &Config, cache_dir: PathBuf, options: &FetchOptions) -> Self {
+        let defaults = FetchSettings::default();
+        let settings = config.fetch.as_ref().unwrap_or(&defaults);
+        Fetcher {
+            cache_dir,
+            repositories: configured_repositories(config),
+            client: reqwest::Client::builder()
+                .user_agent(concat!("rrrGradle/", env!("CARGO_PKG_VERSION")))
+                .connect_timeout(settings.connect_timeout())
+                .read_timeout(settings.read_timeout())
+                .build()
+                .expect("Failed to build HTTP client"),
+            maven_local: settings.maven_local.then(maven_local_repository).flatten(),
+            pool: Semaphore::new(get_max_concurrent_downloads()),
+            checksum_policy: settings.checksums,
+            metadata_ttl: settings.metadata_ttl(),
+            offline: options.offline,
+            refresh: options.refresh,
+            refreshed: Mutex::new(HashSet::new()),
+            missing: Mutex::new(BTreeSet::new()),
+            retries: settings.retries(),
+            failed: Mutex::new(BTreeSet::new()),
+            pom_repositories: settings.pom_repositories,
+            pom_repository_hosts: settings
+                .pom_repository_hosts
+                .iter()
+                .filter_map(|host| match Pattern::new(&host.to_lowercase()) {
+                    Ok(p) => Some(p),
+                    Err(e) => {
+                        eprintln!("⚠️  Ignoring invalid pom_repository_hosts entry '{host}': {e}");
+                        None
+                    }
+                })
+                .collect(),
+            ignored_repositories: Mutex::new(HashSet::new()),
+            models: ModelCache::default(),
+        }
     }
 
-    let mut response = match reqwest::get(url).await {
-        Ok(resp) if resp.status().is_success() => resp,
-        Ok(resp) if resp.status().as_u16() == 404 => {
-            eprintln!("[WARN] 404 Not Found, skipping: {url}");
-            return;
+    /// The repositories `owner`'s POM declares that its subtree may use: with
+    /// `[fetch] pom_repositories` on, HTTPS ones on a `pom_repository_hosts` host that
+    /// are not configured already. Each one left out is reported once.
+    pub fn pom_repositories(&self, declared: &[PomRepository], owner: &str) -> Vec<Repository> {
+        let mut allowed = Vec::new();
+        for repository in declared {
+            let url = repository.url.trim_end_matches('/');
+            // Many POMs redeclare Maven's own `central`
+            if repository.id == "central" || self.repositories.iter().any(|r| r.url == url) {
+                continue;
+            }
+            let refusal = if !self.pom_repositories {
+                "set [fetch] pom_repositories = true to use it"
+            } else {
+                match https_host(url) {
+                    None => "only HTTPS repositories are used",
+                    Some(host) if self.pom_repository_hosts.iter().any(|p| p.matches(&host)) => {
+                        allowed.push(Repository::declared(url));
+                        continue;
+                    }
+                    Some(_) => "its host is not in [fetch] pom_repository_hosts",
+                }
+            };
+            if self
+                .ignored_repositories
+                .lock()
+                .unwrap()
+                .insert(url.to_string())
+            {
+                eprintln!(
+                    "⚠️  Ignoring repository {} ({url}) declared by {owner}: {refusal}",
+                    repository.id
+                );
+            }
         }
-        _ => {
-            eprintln!("⚠️  Failed to fetch: {url}");
-            return;
+        allowed
+    }
+
+    /// Whether an existing cached `path` may be used as is; with `--refresh-dependencies`
+    /// only once it has been downloaded again during this run.
+    fn is_fresh(&self, path: &Path) -> bool {
+        path.exists() && (!self.refresh || !self.refreshed.lock().unwrap().insert(path.into()))
+    }
+
+    /// Repository-relative paths `--offline` could not find, sorted.
+    pub fn missing(&self) -> Vec<String> {
+        self.missing.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Repository-relative paths whose download failed for good, sorted.
+    pub fn failed(&self) -> Vec<String> {
+        self.failed.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Download the repository-relative `rel_path` of an artifact in `group_id` and save it
+    /// to `path`, unless it already exists. The local Maven repository is checked first
+    /// when enabled, except on a refresh; then repositories whose content filter admits
+    /// the group are tried in order, moving on to the next one on a 404, and after them
+    /// the `declared` ones a dependency's POM contributed. Offline, only `file://`
+    /// repositories are tried.
+    /// Returns whether the file is now available.
+    pub async fn fetch_file(
+        &self,
+        group_id: &str,
+        rel_path: &str,
+        path: &Path,
+        is_test: bool,
+        declared: &[Repository],
+    ) -> bool {
+        match self
+            .try_fetch_file(group_id, rel_path, path, is_test, declared)
+            .await
+        {
+            Download::Done => return true,
+            Download::NotFound if self.offline => {
+                self.missing.lock().unwrap().insert(rel_path.to_string());
+            }
+            Download::NotFound => eprintln!("⚠️  Not found in any repository: {rel_path}"),
+            Download::Retry | Download::Failed => {
+                self.failed.lock().unwrap().insert(rel_path.to_string());
+            }
         }
-    };
+        false
+    }
 
-    let total_size = response.content_length();
-    let mut file = match async_fs::File::create(path).await {
-        Ok(f) => f,
-        Err(_) => {
-            eprintln!("⚠️  Failed to create file: {}", path.display());
-            return;
+    /// [`Fetcher::fetch_file`] for a file that may not exist, such as a `-sources` JAR:
+    /// when no repository has it, nothing is reported, and when its download fails that
+    /// is only a warning; either way the fetch does not fail.
+    pub async fn fetch_optional_file(
+        &self,
+        group_id: &str,
+        rel_path: &str,
+        path: &Path,
+        is_test: bool,
+        declared: &[Repository],
+    ) -> bool {
+        match self
+            .try_fetch_file(group_id, rel_path, path, is_test, declared)
+            .await
+        {
+            Download::Done => true,
+            Download::NotFound => false,
+            Download::Retry | Download::Failed => {
+                eprintln!("⚠️  Could not download {rel_path}; skipping it");
+                false
+            }
         }
-    };
-    let mut downloaded: u64 = 0;
-    use std::cmp::min;
-    use std::io::Write;
-    use std::io::stdout;
-
-    while let Ok(Some(chunk)) = response.chunk().await {
-        if let Err(_) = file.write_all(&chunk).await {
-            eprintln!("⚠️  Failed to write to file: {}", path.display());
-            return;
-        }
-        downloaded += chunk.len() as u64;
-        if let Some(total) = total_size {
-            let percent = min(100, (downloaded * 100 / total) as u64);
-            print!(
-                "\rDownloading: {} [{:3}%] ({})",
-                path.file_name().unwrap().to_string_lossy(),
-                percent,
+    }
+
+    /// Try the cache and every repository for the file: `Done` once it is available,
+    /// `NotFound` when none of them has it, `Failed` when it could not be stored or a
+    /// repository's download failed for good.
+    async fn try_fetch_file(
+        &self,
+        group_id: &str,
+        rel_path: &str,
+        path: &Path,
+        is_test: bool,
+        declared: &[Repository],
+    ) -> Download {
+        if self.is_fresh(path) {
+            eprintln!(
+                "✔️  Cached: {} ({})",
+                path.display(),
                 if is_test { "test" } else { "main" }
             );
-            let _ = stdout().flush();
+            return Download::Done;
+        }
+        if let Some(parent) = path.parent()
+            && let Err(e) = async_fs::create_dir_all(parent).await
+        {
+            eprintln!("⚠️  Failed to create {}: {}", parent.display(), e);
+            return Download::Failed;
         }
+
+        // A refresh revalidates against the repositories, so `~/.m2` cannot stand in
+        if !self.refresh
+            && let Some(local) = self.maven_local.as_ref().map(|m2| m2.join(rel_path))
+            && local.is_file()
+        {
+            // Maven already verified it when it was downloaded there; copy through a
+            // `.part` file so an interrupted copy never looks like a cached artifact
+            let part = part_path(path);
+            let copied = match async_fs::copy(&local, &part).await {
+                Ok(_) => async_fs::rename(&part, path).await,
+                Err(e) => Err(e),
+            };
+            match copied {
+                Ok(()) => {
+                    eprintln!("✔️  Maven local: {}", local.display());
+                    return Download::Done;
+                }
+                Err(e) => {
+                    eprintln!("⚠️  Failed to copy {}: {}", local.display(), e);
+                    let _ = async_fs::remove_file(&part).await;
+                }
+            }
+        }
+
+        let repositories = self
+            .repositories
+            .iter()
+            .filter(|r| r.serves(group_id))
+            .chain(declared)
+            .filter(|r| !self.offline || local_path(&r.url).is_some());
+        for repo in repositories {
+            match self.download(repo, rel_path, path, is_test).await {
+                Download::NotFound => continue,
+                outcome => return outcome,
+            }
+        }
+        Download::NotFound
     }
-    if total_size.is_some() {
-        println!(
-            "\rDownloading: {} [100%] ({})",
-            path.file_name().unwrap().to_string_lossy(),
-            if is_test { "test" } else { "main" }
-        );
+
+    /// `maven-metadata.xml` of the repository directory `rel_dir` (an artifact, or one of
+    /// its SNAPSHOT versions), merged across the repositories serving `group_id` and the
+    /// `declared` ones. Each repository's copy is cached as `maven-metadata-<repo>.xml` and
+    /// reused while it is younger than the metadata TTL; offline, any cached copy is used.
+    pub async fn fetch_metadata(
+        &self,
+        group_id: &str,
+        rel_dir: &str,
+        declared: &[Repository],
+    ) -> Option<Metadata> {
+        let mut merged: Option<Metadata> = None;
+        let repositories = self
+            .repositories
+            .iter()
+            .filter(|r| r.serves(group_id))
+            .chain(declared);
+        for repo in repositories {
+            let Some(xml) = self.read_metadata(repo, rel_dir).await else {
+                continue;
+            };
+            let Some(metadata) = parse_metadata(&xml) else {
+                eprintln!(
+                    "⚠️  Ignoring unreadable metadata for {rel_dir} from {}",
+                    repo.name
+                );
+                continue;
+            };
+            match &mut merged {
+                Some(merged) => merged.merge(metadata),
+                None => merged = Some(metadata),
+            }
+        }
+        if merged.is_none() && self.offline {
+            self.missing
+                .lock()
+                .unwrap()
+                .insert(format!("{rel_dir}/maven-metadata.xml"));
+        }
+        merged
     }
-}
 
-/// Download JAR and POM for a given dependency (group:artifact), then parse transitive dependencies (async, iterative).
-async fn fetch_jar_and_pom_async(
-    root_dep: String,
-    root_version: String,
-    is_test: bool,
-    cache_dir: PathBuf,
-    visited: Arc<tokio::sync::Mutex<HashSet<String>>>,
-    pool: Arc<tokio::sync::Semaphore>,
-) {
-    let mut stack = VecDeque::new();
-    stack.push_back((root_dep, root_version));
-
-    while let Some((dep, version)) = stack.pop_front() {
-        let key = format!("{dep}:{version}");
-        let already_fetched = {
-            let mut v = visited.lock().await;
-            !v.insert(key.clone())
+    /// One repository's `maven-metadata.xml` for `rel_dir`, from the cache when still fresh.
+    async fn read_metadata(&self, repo: &Repository, rel_dir: &str) -> Option<String> {
+        let url = repo.url_for(&format!("{rel_dir}/maven-metadata.xml"));
+        if local_path(&url).is_some() {
+            return self.read_text(repo, &url).await;
+        }
+
+        let cached = self
+            .cache_dir
+            .join(rel_dir)
+            .join(format!("maven-metadata-{}.xml", repo.name));
+        let within_ttl = fs::metadata(&cached)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| self.offline || age < self.metadata_ttl);
+        if within_ttl && self.is_fresh(&cached) {
+            return async_fs::read_to_string(&cached).await.ok();
+        }
+        if self.offline {
+            return None;
+        }
+
+        let xml = self.read_text(repo, &url).await?;
+        let part = part_path(&cached);
+        let saved = async {
+            async_fs::create_dir_all(cached.parent().unwrap()).await?;
+            async_fs::write(&part, &xml).await?;
+            async_fs::rename(&part, &cached).await
         };
-        if already_fetched {
-            continue;
+        if let Err(e) = saved.await {
+            eprintln!("⚠️  Failed to cache {}: {}", cached.display(), e);
+        }
+        Some(xml)
+    }
+
+    /// Download `rel_path` from `repo` into `path`, retrying transient failures with
+    /// exponential backoff (0.5s, 1s, 2s, ...) up to the configured number of retries.
+    /// Each attempt holds a download slot, the backoff in between does not, so a flaky
+    /// repository never keeps other downloads waiting.
+    async fn download(
+        &self,
+        repo: &Repository,
+        rel_path: &str,
+        path: &Path,
+        is_test: bool,
+    ) -> Download {
+        let mut delay = Duration::from_millis(500);
+        let mut attempt = 0;
+        loop {
+            let outcome = {
+                let _permit = self.pool.acquire().await.unwrap();
+                self.try_download(repo, rel_path, path, is_test).await
+            };
+            match outcome {
+                Download::Retry if attempt < self.retries => {
+                    attempt += 1;
+                    eprintln!(
+                        "↻ Retrying {rel_path} from {} in {:.1}s ({attempt}/{})",
+                        repo.name,
+                        delay.as_secs_f32(),
+                        self.retries
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                outcome => return outcome,
+            }
         }
+    }
 
-        let (group_id, artifact_id) = if let Some(idx) = dep.find(':') {
-            (&dep[..idx], &dep[idx + 1..])
+    /// One attempt at downloading `rel_path` from `repo` into `path`.
+    ///
+    /// The body goes to a `.part` file that is renamed into place only once it has been
+    /// verified against the repository's checksum sidecar, so an interrupted or corrupted
+    /// download never ends up in the cache. A `.part` left by an interrupted attempt is
+    /// resumed with an HTTP range request; servers that ignore the range send it all again.
+    /// A resumed file that does not match its checksum is discarded and downloaded whole
+    /// on the next attempt, whatever the checksum policy.
+    async fn try_download(
+        &self,
+        repo: &Repository,
+        rel_path: &str,
+        path: &Path,
+        is_test: bool,
+    ) -> Download {
+        let url = repo.url_for(rel_path);
+        let url = url.as_str();
+        let part_path = part_path(path);
+        let mut resumed = false;
+
+        if let Some(source) = local_path(url) {
+            if !source.is_file() {
+                return Download::NotFound;
+            }
+            if let Err(e) = async_fs::copy(&source, &part_path).await {
+                eprintln!("⚠️  Failed to copy {}: {}", source.display(), e);
+                return Download::Failed;
+            }
         } else {
-            ("?", "?")
-        };
+            let resume_from = async_fs::metadata(&part_path)
+                .await
+                .map(|m| m.len())
+                .unwrap_or(0);
+            let mut request = repo.authorize(self.client.get(url));
+            if resume_from > 0 {
+                request = request.header(RANGE, format!("bytes={resume_from}-"));
+            }
+            let mut response = match request.send().await {
+                Ok(resp) if resp.status().is_success() => resp,
+                Ok(resp) if resp.status() == StatusCode::NOT_FOUND => return Download::NotFound,
+                Ok(resp) if matches!(resp.status().as_u16(), 401 | 403) => {
+                    report_unauthorized(repo, resp.status());
+                    return Download::Failed;
+                }
+                Ok(resp) if resp.status() == StatusCode::RANGE_NOT_SATISFIABLE => {
+                    // The partial file does not fit what the server has now; start over
+                    let _ = async_fs::remove_file(&part_path).await;
+                    return Download::Retry;
+                }
+                Ok(resp)
+                    if resp.status().is_server_error()
+                        || resp.status() == StatusCode::TOO_MANY_REQUESTS =>
+                {
+                    eprintln!("⚠️  {} from {}: {url}", resp.status(), repo.name);
+                    return Download::Retry;
+                }
+                Ok(resp) => {
+                    eprintln!("⚠️  {} from {}: {url}", resp.status(), repo.name);
+                    return Download::Failed;
+                }
+                Err(e) => {
+                    eprintln!("⚠️  Failed to fetch from {}: {url} ({e})", repo.name);
+                    return Download::Retry;
+                }
+            };
+
+            resumed = response.status() == StatusCode::PARTIAL_CONTENT;
+            let opened = if resumed {
+                async_fs::OpenOptions::new()
+                    .append(true)
+                    .open(&part_path)
+                    .await
+            } else {
+                async_fs::File::create(&part_path).await
+            };
+            let mut file = match opened {
+                Ok(f) => f,
+                Err(_) => {
+                    eprintln!("⚠️  Failed to create file: {}", part_path.display());
+                    return Download::Failed;
+                }
+            };
+            let mut downloaded: u64 = if resumed { resume_from } else { 0 };
+            let total_size = response.content_length().map(|len| len + downloaded);
+            use std::cmp::min;
+            use std::io::Write;
+            use std::io::stderr;
 
-        let (base_url, jar_name, pom_name) = match dep_to_url(&dep, &version) {
-            Some(t) => t,
-            None => {
+            loop {
+                match response.chunk().await {
+                    Ok(Some(chunk)) => {
+                        if file.write_all(&chunk).await.is_err() {
+                            eprintln!("⚠️  Failed to write to file: {}", part_path.display());
+                            let _ = async_fs::remove_file(&part_path).await;
+                            return Download::Failed;
+                        }
+                        downloaded += chunk.len() as u64;
+                        if let Some(total) = total_size {
+                            let percent = min(100, downloaded * 100 / total);
+                            eprint!(
+                                "\rDownloading: {} [{:3}%] ({})",
+                                path.file_name().unwrap().to_string_lossy(),
+                                percent,
+                                if is_test { "test" } else { "main" }
+                            );
+                            let _ = stderr().flush();
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(_) => {
+                        // Keep what arrived so the next attempt can resume from it
+                        let _ = file.flush().await;
+                        eprintln!("\n⚠️  Download interrupted after {downloaded} bytes: {url}");
+                        return Download::Retry;
+                    }
+                }
+            }
+            if total_size.is_some() {
                 eprintln!(
-                    "[DEBUG] Invalid dep_to_url for: {}:{}:{}",
-                    group_id, artifact_id, version
+                    "\rDownloading: {} [100%] ({})",
+                    path.file_name().unwrap().to_string_lossy(),
+                    if is_test { "test" } else { "main" }
                 );
-                continue;
             }
-        };
+            if file.flush().await.is_err() {
+                eprintln!("⚠️  Failed to write to file: {}", part_path.display());
+                let _ = async_fs::remove_file(&part_path).await;
+                return Download::Failed;
+            }
+            if total_size.is_some_and(|total| total != downloaded) {
+                eprintln!("⚠️  Incomplete download: {url}");
+                return Download::Retry;
+            }
+        }
 
-        let jar_url = format!("{base_url}/{}", jar_name);
-        let pom_url = format!("{base_url}/{}", pom_name);
+        match self.verify_checksum(repo, url, &part_path, resumed).await {
+            Checked::Keep => {}
+            Checked::Reject => {
+                let _ = async_fs::remove_file(&part_path).await;
+                return Download::Failed;
+            }
+            Checked::Restart => {
+                let _ = async_fs::remove_file(&part_path).await;
+                return Download::Retry;
+            }
+        }
+        if async_fs::rename(&part_path, path).await.is_err() {
+            eprintln!("⚠️  Failed to move {} into place", part_path.display());
+            let _ = async_fs::remove_file(&part_path).await;
+            return Download::Failed;
+        }
+        Download::Done
+    }
 
-        let jar_path = cache_dir.join(&jar_name);
-        let pom_path = cache_dir.join(&pom_name);
+    /// Small text resource such as a checksum sidecar, from HTTP or a `file://` repository.
+    /// A refused request is explained like a refused download, not taken for a missing file.
+    async fn read_text(&self, repo: &Repository, url: &str) -> Option<String> {
+        if let Some(path) = local_path(url) {
+            return async_fs::read_to_string(path).await.ok();
+        }
+        match repo.authorize(self.client.get(url)).send().await {
+            Ok(resp) if resp.status().is_success() => resp.text().await.ok(),
+            Ok(resp) if matches!(resp.status().as_u16(), 401 | 403) => {
+                report_unauthorized(repo, resp.status());
+                None
+            }
+            _ => None,
+        }
+    }
 
-        println!(
-            "→ Downloading {}:{} ({})",
-            dep,
-            version,
-            if is_test { "test" } else { "main" }
-        );
+    /// Check a downloaded file against the strongest checksum sidecar the repository
+    /// publishes, and decide under the checksum policy whether to keep it. A `resumed`
+    /// file that does not match is never kept: its start may come from an older upload.
+    async fn verify_checksum(
+        &self,
+        repo: &Repository,
+        url: &str,
+        file: &Path,
+        resumed: bool,
+    ) -> Checked {
+        if self.checksum_policy == ChecksumPolicy::Ignore {
+            return Checked::Keep;
+        }
 
-        let _permit = pool.acquire().await.unwrap();
-        let f1 = fetch_file_async(&jar_url, &jar_path, is_test);
-        let f2 = fetch_file_async(&pom_url, &pom_path, is_test);
-        futures::future::join(f1, f2).await;
-
-        if pom_path.exists() {
-            let model = parse_pom_model(pom_path.to_str().unwrap());
-            let deps: Vec<_> = model
-                .dependencies
-                .into_iter()
-                .filter(|dep| dep.scope.as_deref() != Some("test") && !dep.optional)
-                .map(|dep| {
-                    (
-                        dep.group_id.clone(),
-                        dep.artifact_id.clone(),
-                        dep.version.clone(),
-                    )
-                })
-                .collect();
-            for (group_id, artifact_id, version) in deps {
-                let sub = format!("{}:{}", group_id, artifact_id);
-                stack.push_back((sub, version));
+        for algorithm in ChecksumAlgorithm::PREFERENCE {
+            let sidecar_url = format!("{url}.{}", algorithm.extension());
+            let Some(expected) = self
+                .read_text(repo, &sidecar_url)
+                .await
+                .and_then(|text| parse_sidecar(&text))
+            else {
+                continue;
+            };
+
+            let actual = match file_digest(file, algorithm) {
+                Ok(digest) => digest,
+                Err(_) => {
+                    eprintln!("⚠️  Failed to read {} for verification", file.display());
+                    return Checked::Reject;
+                }
+            };
+            if actual == expected {
+                return Checked::Keep;
+            }
+            if resumed {
+                eprintln!(
+                    "⚠️  {} checksum mismatch for resumed {url}; downloading it again",
+                    algorithm.extension()
+                );
+                return Checked::Restart;
             }
+            return match self.checksum_policy {
+                ChecksumPolicy::Fail => {
+                    eprintln!(
+                        "✗ {} checksum mismatch for {url} (expected {expected}, got {actual})",
+                        algorithm.extension()
+                    );
+                    Checked::Reject
+                }
+                _ => {
+                    eprintln!(
+                        "⚠️  {} checksum mismatch for {url} (expected {expected}, got {actual})",
+                        algorithm.extension()
+                    );
+                    Checked::Keep
+                }
+            };
+        }
+
+        match self.checksum_policy {
+            ChecksumPolicy::Fail => {
+                eprintln!("✗ No checksum published for {url}");
+                Checked::Reject
+            }
+            _ => {
+                eprintln!("⚠️  No checksum published for {url}");
+                Checked::Keep
+            }
+        }
+    }
+}
+
+/// Explain a 401/403 from `repo` and where its credentials come from.
+fn report_unauthorized(repo: &Repository, status: reqwest::StatusCode) {
+    let prefix = env_prefix(&repo.name);
+    let file = credentials_path()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "~/.rrrgradle/credentials.toml".to_string());
+    if repo.auth.is_some() {
+        eprintln!(
+            "✗ Repository '{}' rejected the configured credentials ({status}); check {prefix}_* or [{}] in {file}",
+            repo.name, repo.name
+        );
+    } else {
+        eprintln!(
+            "✗ Repository '{}' requires authentication ({status}); set {prefix}_USERNAME/{prefix}_PASSWORD or {prefix}_TOKEN, or add [{}] to {file}",
+            repo.name, repo.name
+        );
+    }
+}
+
+/// Take the exclusive lock on the shared cache. It is held for the whole `fetch`, so
+/// concurrent runs from other projects wait rather than downloading the same files at once.
+fn lock_cache(cache_dir: &Path) -> io::Result<fs::File> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(cache_dir.join(".lock"))?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            eprintln!("⏳ Waiting for another rrrGradle process to release the cache lock...");
+            file.lock()?;
         }
+        Err(TryLockError::Error(e)) => return Err(e),
     }
+    Ok(file)
+}
+
+/// A fetcher over the shared cache, with the cache lock that must be held while it is used.
+pub fn open_cache(config: &Config, options: &FetchOptions) -> io::Result<(Fetcher, fs::File)> {
+    let cache_dir = cache_root();
+    fs::create_dir_all(&cache_dir)?;
+    let lock = lock_cache(&cache_dir)?;
+    Ok((Fetcher::new(config, cache_dir, options), lock))
+}
+
+/// Temporary download location next to the final `path`
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
 }
 
-/// Converts "group:artifact" into (base_url, jar_name, pom_name)
-fn dep_to_url(dep: &str, version: &str) -> Option<(String, String, String)> {
+/// Converts "group:artifact" into (base_path, artifact_name, pom_name), where the base path
+/// is relative to a repository root and the artifact file is
+/// `{artifact}-{version}[-{classifier}].{extension}`. Timestamped SNAPSHOT versions live
+/// in their `-SNAPSHOT` directory.
+pub fn dep_to_path(
+    dep: &str,
+    version: &str,
+    classifier: Option<&str>,
+    extension: &str,
+) -> Option<(String, String, String)> {
     let parts: Vec<&str> = dep.split(':').collect();
     if parts.len() != 2 {
         return None;
@@ -165,62 +755,573 @@ fn dep_to_url(dep: &str, version: &str) -> Option<(String, String, String)> {
     let (group, artifact) = (parts[0], parts[1]);
 
     let path = group.replace('.', "/");
-    let jar_name = format!("{artifact}-{version}.jar");
+    let jar_name = match classifier {
+        Some(classifier) => format!("{artifact}-{version}-{classifier}.{extension}"),
+        None => format!("{artifact}-{version}.{extension}"),
+    };
     let pom_name = format!("{artifact}-{version}.pom");
-    let base_url = format!(
-        "https://repo1.maven.org/maven2/{}/{}/{}",
-        path, artifact, version
-    );
+    let base_path = format!("{}/{}/{}", path, artifact, base_version(version));
 
-    Some((base_url, jar_name, pom_name))
+    Some((base_path, jar_name, pom_name))
 }
 
-/// Entry point: fetches dependencies listed in config (async, parallel).
-pub async fn fetch_dependencies(config: &Config) {
-    let cache_dir = Path::new(".rrrgradle/cache/").to_path_buf();
-    fs::create_dir_all(&cache_dir).expect("Failed to create cache dir");
+/// Resolve the dependency graph under the cache lock, downloading only the POMs and
+/// metadata resolution needs. For commands that inspect the graph rather than build it.
+pub async fn resolve_dependencies(
+    config: &Config,
+    options: &FetchOptions,
+) -> Result<Resolution, FetchError> {
+    let (fetcher, _lock) = open_cache(config, options)?;
+    let resolution = resolve_graph(config, &fetcher).await?;
+    let missing = fetcher.missing();
+    if !missing.is_empty() {
+        return Err(FetchError::MissingOffline(missing));
+    }
+    let failed = fetcher.failed();
+    if !failed.is_empty() {
+        return Err(FetchError::Failed(failed));
+    }
+    Ok(resolution)
+}
 
-    let visited = Arc::new(tokio::sync::Mutex::new(HashSet::new()));
-    let pool = Arc::new(tokio::sync::Semaphore::new(get_max_concurrent_downloads())); // max 8 concurrent downloads
+/// Download the `-sources` and/or `-javadoc` JARs (`kinds`) of every resolved JAR and
+/// report how many artifacts publish them; one that is not published is no error.
+async fn fetch_attachments(
+    fetcher: &Arc<Fetcher>,
+    resolution: &Resolution,
+    kinds: &[&'static str],
+) {
+    let mut futs = FuturesUnordered::new();
+    let mut requested = HashSet::new();
+    for artifact in resolution.artifacts.iter().filter(|a| a.extension == "jar") {
+        for &kind in kinds {
+            // Classified artifacts share the sources of their main JAR
+            let Some((base_path, name, _)) =
+                dep_to_path(&artifact.dep, &artifact.version, Some(kind), "jar")
+            else {
+                continue;
+            };
+            let rel_path = format!("{base_path}/{name}");
+            if !requested.insert(rel_path.clone()) {
+                continue;
+            }
+            let path = fetcher.cache_dir.join(&base_path).join(&name);
+            let group_id = artifact.group_id().to_string();
+            let is_test = artifact.scope == Scope::Test;
+            let repositories = artifact.repositories.clone();
+            let fetcher = fetcher.clone();
+            let task = tokio::spawn(async move {
+                fetcher
+                    .fetch_optional_file(&group_id, &rel_path, &path, is_test, &repositories)
+                    .await
+            });
+            futs.push(task.map(move |available| (kind, available)));
+        }
+    }
+
+    let mut found: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    while let Some((kind, available)) = futs.next().await {
+        let available = available.unwrap_or_else(|e| {
+            eprintln!("⚠️  A {kind} JAR download stopped: {e}");
+            false
+        });
+        let (published, total) = found.entry(kind).or_default();
+        *published += usize::from(available);
+        *total += 1;
+    }
+    for (kind, (published, total)) in found {
+        println!("✓ {kind} JARs for {published} of {total} artifacts");
+    }
+}
+
+/// Entry point: resolves the dependency graph, downloads the selected JARs (async, parallel)
+/// and records the result in `rrrgradle.lock`.
+pub async fn fetch_dependencies(config: &Config, options: &FetchOptions) -> Result<(), FetchError> {
+    let (fetcher, _lock) = open_cache(config, options)?;
+    let cache_dir = fetcher.cache_dir.clone();
+    let fetcher = Arc::new(fetcher);
+
+    let resolution = resolve_graph(config, &fetcher).await?;
+
+    for conflict in &resolution.conflicts {
+        let downgrade = ComparableVersion::new(&conflict.requested)
+            > ComparableVersion::new(&conflict.selected);
+        println!(
+            "⚠️  Conflict: {}:{} requested by {} → using {}{}",
+            conflict.dep,
+            conflict.requested,
+            conflict.path.join(" → "),
+            conflict.selected,
+            if downgrade {
+                " (older than requested)"
+            } else {
+                ""
+            }
+        );
+    }
 
     let mut futs = FuturesUnordered::new();
+    // `pom`-type dependencies only contribute their dependencies; the POM is already cached
+    for artifact in resolution.artifacts.iter().filter(|a| a.extension != "pom") {
+        let Some((base_path, jar_name, _)) = dep_to_path(
+            &artifact.dep,
+            &artifact.version,
+            artifact.classifier.as_deref(),
+            &artifact.extension,
+        ) else {
+            eprintln!(
+                "⚠️  Not a group:artifact coordinate, skipping: {}:{}",
+                artifact.dep, artifact.version
+            );
+            continue;
+        };
+        let jar_rel_path = format!("{base_path}/{jar_name}");
+        let group_id = artifact.group_id().to_string();
+        let jar_path = cache_dir.join(&base_path).join(&jar_name);
+        let fetcher = fetcher.clone();
+        let is_test = artifact.scope == Scope::Test;
+        let repositories = artifact.repositories.clone();
 
-    // Fetch main dependencies
-    if let Some(deps) = &config.dependencies {
-        for (dep, version) in deps {
-            let visited = visited.clone();
-            let cache_dir = cache_dir.clone();
-            let pool = pool.clone();
-            futs.push(tokio::spawn(fetch_jar_and_pom_async(
-                dep.clone(),
-                version.clone(),
-                false,
-                cache_dir,
-                visited,
-                pool,
-            )));
-        }
-    }
-
-    // Fetch test dependencies
-    if let Some(test_deps) = &config.test_dependencies {
-        println!("Fetching test dependencies...");
-        for (dep, version) in test_deps {
-            let visited = visited.clone();
-            let cache_dir = cache_dir.clone();
-            let pool = pool.clone();
-            futs.push(tokio::spawn(fetch_jar_and_pom_async(
-                dep.clone(),
-                version.clone(),
-                true,
-                cache_dir,
-                visited,
-                pool,
-            )));
-        }
-    }
-
-    while futs.next().await.is_some() {}
+        println!(
+            "→ Downloading {} ({})",
+            artifact.coordinate(),
+            artifact.scope.as_str()
+        );
+
+        let task = tokio::spawn({
+            let jar_rel_path = jar_rel_path.clone();
+            async move {
+                let available = fetcher
+                    .fetch_file(&group_id, &jar_rel_path, &jar_path, is_test, &repositories)
+                    .await;
+                available.then_some(jar_path)
+            }
+        });
+        futs.push(task.map(move |downloaded| (jar_rel_path, downloaded)));
+    }
+
+    let mut downloaded = HashSet::new();
+    // Artifacts no repository had, on top of the downloads the fetcher saw fail
+    let mut failed: BTreeSet<String> = BTreeSet::new();
+    // A task that panicked counts as a failed download; the others still finish
+    while let Some((jar_rel_path, result)) = futs.next().await {
+        match result {
+            Ok(Some(jar_path)) => {
+                downloaded.insert(jar_path);
+            }
+            Ok(None) => {
+                failed.insert(jar_rel_path);
+            }
+            Err(e) => {
+                eprintln!("✗ Download of {jar_rel_path} stopped: {e}");
+                failed.insert(jar_rel_path);
+            }
+        }
+    }
+
+    let kinds: Vec<&'static str> = [("sources", options.sources), ("javadoc", options.javadoc)]
+        .into_iter()
+        .filter_map(|(kind, wanted)| wanted.then_some(kind))
+        .collect();
+    if !kinds.is_empty() {
+        fetch_attachments(&fetcher, &resolution, &kinds).await;
+    }
+    // An attachment fetched by this or an earlier run, as `-sources`/`-javadoc` JAR path
+    let attachment = |artifact: &ResolvedArtifact, kind: &str| {
+        let (base_path, name, _) =
+            dep_to_path(&artifact.dep, &artifact.version, Some(kind), "jar")?;
+        let file = cache_dir.join(base_path).join(name);
+        file.is_file().then(|| file.to_string_lossy().to_string())
+    };
+
+    // Keep the resolver's nearest-first order on the classpath; only JARs belong on it
+    let mut classpath = ResolvedClasspath::default();
+    let mut locked = Vec::new();
+    for artifact in &resolution.artifacts {
+        let Some((base_path, jar_name, pom_name)) = dep_to_path(
+            &artifact.dep,
+            &artifact.version,
+            artifact.classifier.as_deref(),
+            &artifact.extension,
+        ) else {
+            continue;
+        };
+        let file = cache_dir
+            .join(&base_path)
+            .join(if artifact.extension == "pom" {
+                &pom_name
+            } else {
+                &jar_name
+            });
+        if artifact.extension == "jar" && downloaded.contains(&file) {
+            classpath.entries.push(ClasspathEntry {
+                path: file.to_string_lossy().to_string(),
+                scope: artifact.scope,
+                sources: attachment(artifact, "sources"),
+                javadoc: attachment(artifact, "javadoc"),
+            });
+        }
+        locked.push(LockedArtifact {
+            id: artifact.coordinate(),
+            dep_type: artifact.extension.clone(),
+            scope: artifact.scope,
+            path: artifact.path.clone(),
+            sha256: file_digest(&file, ChecksumAlgorithm::Sha256).ok(),
+        });
+    }
+
+    let missing = fetcher.missing();
+    if !missing.is_empty() {
+        return Err(FetchError::MissingOffline(missing));
+    }
+    failed.extend(fetcher.failed());
+    if !failed.is_empty() {
+        return Err(FetchError::Failed(failed.into_iter().collect()));
+    }
+
+    let lock = LockFile::new(locked);
+    if options.locked {
+        let current = read_lock_file().ok_or(FetchError::MissingLockFile)?;
+        let changes = current.diff(&lock);
+        if !changes.is_empty() {
+            return Err(FetchError::OutdatedLockFile(changes));
+        }
+    } else {
+        write_lock_file(&lock)?;
+    }
+    write_resolved_classpath(&classpath);
 
     println!("✓ Dependency resolution complete.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, TempDir, http_server, repository_url};
+    use sha1::Digest;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn fetcher(cache: &TempDir) -> Fetcher {
+        let config = fixtures::config(&format!(
+            r#"
+            [fetch]
+            checksums = "ignore"
+
+            [[repositories]]
+            name = "internal"
+            url = "{}"
+            groups = ["com.example"]
+
+            [[repositories]]
+            name = "public"
+            url = "{}"
+            "#,
+            repository_url("internal"),
+            repository_url("public"),
+        ));
+        fixtures::fetcher(&config, cache)
+    }
+
+    async fn fetch(fetcher: &Fetcher, group_id: &str, rel_path: &str) -> Option<String> {
+        let path = fetcher.cache_dir.join(rel_path);
+        fetcher
+            .fetch_file(group_id, rel_path, &path, false, &[])
+            .await
+            .then(|| fs::read_to_string(path).unwrap().trim().to_string())
+    }
+
+    #[tokio::test]
+    async fn looks_up_repositories_in_order_within_their_groups() {
+        let cache = TempDir::new("fetcher-order");
+        let fetcher = fetcher(&cache);
+
+        // Both have it; the first one listed wins
+        let lib = fetch(&fetcher, "com.example", "com/example/lib/1.0/lib-1.0.jar").await;
+        assert_eq!(lib.as_deref(), Some("internal"));
+        // Only the second has it: a 404 moves on
+        let extra = fetch(
+            &fetcher,
+            "com.example",
+            "com/example/extra/1.0/extra-1.0.jar",
+        )
+        .await;
+        assert_eq!(extra.as_deref(), Some("public"));
+        // The first has it, but is not asked for groups outside its filter
+        let tool = fetch(&fetcher, "org.acme", "org/acme/tool/1.0/tool-1.0.jar").await;
+        assert_eq!(tool.as_deref(), Some("public"));
+        // Nowhere: not found, which is not a failed download
+        let none = fetch(&fetcher, "com.example", "com/example/none/1.0/none-1.0.jar").await;
+        assert_eq!(none, None);
+        assert!(fetcher.failed().is_empty());
+    }
+
+    /// A fetcher over the given repository URLs, in order, retrying once.
+    fn http_fetcher(urls: &[&str], cache: &TempDir) -> Fetcher {
+        checked_http_fetcher(urls, "ignore", cache)
+    }
+
+    fn checked_http_fetcher(urls: &[&str], checksums: &str, cache: &TempDir) -> Fetcher {
+        let repositories: String = urls
+            .iter()
+            .enumerate()
+            .map(|(i, url)| format!("[[repositories]]\nname = \"repo{i}\"\nurl = \"{url}\"\n"))
+            .collect();
+        let config = fixtures::config(&format!(
+            "[fetch]\nchecksums = \"{checksums}\"\nretries = 1\n\n{repositories}"
+        ));
+        fixtures::fetcher(&config, cache)
+    }
+
+    const JAR: &str = "com/example/lib/1.0/lib-1.0.jar";
+
+    #[tokio::test]
+    async fn http_404_falls_through_to_the_next_repository() {
+        let asked = Arc::new(AtomicUsize::new(0));
+        let counter = asked.clone();
+        let empty = http_server(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (404, Vec::new())
+        })
+        .await;
+        let full = http_server(|_, _| (200, b"second".to_vec())).await;
+        let cache = TempDir::new("fetcher-http-404");
+        let fetcher = http_fetcher(&[&empty, &full], &cache);
+
+        assert_eq!(
+            fetch(&fetcher, "com.example", JAR).await.as_deref(),
+            Some("second")
+        );
+        assert_eq!(asked.load(Ordering::SeqCst), 1);
+        assert!(fetcher.failed().is_empty());
+    }
+
+    #[tokio::test]
+    async fn http_server_errors_are_retried() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let flaky = http_server(move |path, _| {
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            match path {
+                // Recovers on the second attempt
+                p if p.ends_with("lib-1.0.jar") && n > 0 => (200, b"recovered".to_vec()),
+                _ => (503, Vec::new()),
+            }
+        })
+        .await;
+        let cache = TempDir::new("fetcher-http-retry");
+        let fetcher = http_fetcher(&[&flaky], &cache);
+
+        assert_eq!(
+            fetch(&fetcher, "com.example", JAR).await.as_deref(),
+            Some("recovered")
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // Still failing once the retries are used up: a failed download, not a missing one
+        let down = "com/example/down/1.0/down-1.0.jar";
+        assert_eq!(fetch(&fetcher, "com.example", down).await, None);
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+        assert_eq!(fetcher.failed(), [down]);
+    }
+
+    #[tokio::test]
+    async fn http_unauthorized_fails_without_retrying() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let private = http_server(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (401, Vec::new())
+        })
+        .await;
+        let public = http_server(|_, _| (200, b"public".to_vec())).await;
+        let cache = TempDir::new("fetcher-http-401");
+        let fetcher = http_fetcher(&[&private, &public], &cache);
+
+        // Unlike a 404, a refusal does not move on to the next repository
+        assert_eq!(fetch(&fetcher, "com.example", JAR).await, None);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert_eq!(fetcher.failed(), [JAR]);
+    }
+
+    #[tokio::test]
+    async fn http_unsatisfiable_range_starts_over() {
+        let server = http_server(|_, head| {
+            if head.to_lowercase().contains("range:") {
+                (416, Vec::new())
+            } else {
+                (200, b"whole".to_vec())
+            }
+        })
+        .await;
+        let cache = TempDir::new("fetcher-http-416");
+        let fetcher = http_fetcher(&[&server], &cache);
+        let path = cache.path().join(JAR);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(part_path(&path), "stale partial download").unwrap();
+
+        assert_eq!(
+            fetch(&fetcher, "com.example", JAR).await.as_deref(),
+            Some("whole")
+        );
+        assert!(!part_path(&path).exists());
+    }
+
+    #[tokio::test]
+    async fn resumed_download_that_fails_verification_starts_over() {
+        let sha1 = hex::encode(sha1::Sha1::digest(b"whole file"));
+        let ranges = Arc::new(AtomicUsize::new(0));
+        let counter = ranges.clone();
+        let server = http_server(move |path, head| {
+            if path.ends_with(".sha1") {
+                (200, sha1.clone().into_bytes())
+            } else if head.to_lowercase().contains("range:") {
+                counter.fetch_add(1, Ordering::SeqCst);
+                // The rest of a newer upload than the partial file came from
+                (206, b"file".to_vec())
+            } else {
+                (200, b"whole file".to_vec())
+            }
+        })
+        .await;
+        let cache = TempDir::new("fetcher-http-resume");
+        let path = cache.path().join(JAR);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(part_path(&path), "stale ").unwrap();
+
+        // Even the lenient policy never keeps a resumed file that does not match
+        let fetcher = checked_http_fetcher(&[&server], "warn", &cache);
+        assert_eq!(
+            fetch(&fetcher, "com.example", JAR).await.as_deref(),
+            Some("whole file")
+        );
+        assert_eq!(ranges.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn checksum_policy_matrix() {
+        let content = b"verified";
+        let sha256 = hex::encode(sha2::Sha256::digest(content)).to_uppercase();
+        let server = http_server(move |path, _| match path {
+            p if p.starts_with("/good/") && p.ends_with(".sha256") => {
+                (200, format!("{sha256}  lib-1.0.jar\n").into_bytes())
+            }
+            p if p.starts_with("/bad/") && p.ends_with(".sha1") => (200, b"0badc0de".to_vec()),
+            _ => (404, Vec::new()),
+        })
+        .await;
+        let cache = TempDir::new("fetcher-checksum-matrix");
+        let file = cache.path().join("lib-1.0.jar");
+        fs::write(&file, content).unwrap();
+        let repo = Repository::new("repo0", &server, &[]);
+
+        use Checked::{Keep, Reject, Restart};
+        for (policy, sidecar, resumed, expected) in [
+            ("ignore", "good", false, Keep),
+            ("ignore", "bad", false, Keep),
+            ("ignore", "bad", true, Keep),
+            ("ignore", "none", false, Keep),
+            ("warn", "good", false, Keep),
+            ("warn", "bad", false, Keep),
+            ("warn", "bad", true, Restart),
+            ("warn", "none", false, Keep),
+            ("fail", "good", false, Keep),
+            ("fail", "good", true, Keep),
+            ("fail", "bad", false, Reject),
+            ("fail", "bad", true, Restart),
+            ("fail", "none", false, Reject),
+        ] {
+            let fetcher = checked_http_fetcher(&[&server], policy, &cache);
+            let url = format!("{server}/{sidecar}/{JAR}");
+            assert_eq!(
+                fetcher.verify_checksum(&repo, &url, &file, resumed).await,
+                expected,
+                "{policy} {sidecar} resumed={resumed}"
+            );
+        }
+    }
+
+    fn declared(id: &str, url: &str) -> PomRepository {
+        PomRepository {
+            id: id.to_string(),
+            url: url.to_string(),
+        }
+    }
+
+    fn pom_repository_fetcher(settings: &str, cache: &TempDir) -> Fetcher {
+        let config = fixtures::config(&format!(
+            r#"
+            [fetch]
+            {settings}
+
+            [[repositories]]
+            name = "internal"
+            url = "https://internal.example.com/maven"
+            "#
+        ));
+        fixtures::fetcher(&config, cache)
+    }
+
+    #[test]
+    fn pom_repositories_are_https_only_and_on_allowed_hosts() {
+        let cache = TempDir::new("fetcher-pom-repositories");
+        let fetcher = pom_repository_fetcher(
+            r#"pom_repositories = true
+            pom_repository_hosts = ["repo.example.com", "*.JBoss.org"]"#,
+            &cache,
+        );
+        let declared = [
+            declared("central", "https://repo.example.com/central"),
+            declared("mirror", "https://internal.example.com/maven/"),
+            declared("plain", "http://repo.example.com/plain"),
+            declared("vendor", "https://REPO.example.com/vendor/"),
+            declared("jboss", "https://repository.jboss.org/nexus"),
+            declared("elsewhere", "https://repo.example.net/maven"),
+        ];
+
+        let allowed: Vec<_> = fetcher
+            .pom_repositories(&declared, "com.example:lib:1.0")
+            .into_iter()
+            .map(|r| r.url)
+            .collect();
+        assert_eq!(
+            allowed,
+            [
+                "https://REPO.example.com/vendor",
+                "https://repository.jboss.org/nexus"
+            ]
+        );
+    }
+
+    #[test]
+    fn pom_repositories_are_off_by_default() {
+        let cache = TempDir::new("fetcher-pom-repositories-off");
+        let fetcher = pom_repository_fetcher("", &cache);
+        let declared = [declared("vendor", "https://repo.example.com/vendor")];
+
+        assert!(
+            fetcher
+                .pom_repositories(&declared, "com.example:lib:1.0")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn pom_repositories_never_get_credentials() {
+        let cache = TempDir::new("fetcher-pom-repositories-auth");
+        let fetcher = pom_repository_fetcher(
+            r#"pom_repositories = true
+            pom_repository_hosts = ["*.example.com"]"#,
+            &cache,
+        );
+        // Named like the configured repository, whose credentials are looked up by name
+        let declared = [declared("internal", "https://other.example.com/maven")];
+
+        let [repository] = fetcher
+            .pom_repositories(&declared, "com.example:lib:1.0")
+            .try_into()
+            .unwrap();
+        assert_eq!(repository.name, "other.example.com_maven");
+        assert!(repository.auth.is_none());
+    }
 }
@@ -0,0 +1,99 @@
+use crate::config::Config;
+use crate::fetch::{FetchOptions, Fetcher};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// A directory under `tests/fixtures`.
+pub fn fixture_dir(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// `file://` URL of a repository under `tests/fixtures/repos`.
+pub fn repository_url(name: &str) -> String {
+    format!("file://{}", fixture_dir("repos").join(name).display())
+}
+
+/// A project config: a `[project]` table followed by `tables`, given as TOML.
+pub fn config(tables: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+        [project]
+        name = "demo"
+        version = "1.0"
+        main_class = "Main"
+
+        {tables}
+        "#
+    ))
+    .unwrap()
+}
+
+/// A scratch directory for one test, removed again when dropped.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(test: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("rrrgradle-{}-{test}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A fetcher for `config` that caches into `cache`.
+pub fn fetcher(config: &Config, cache: &TempDir) -> Fetcher {
+    Fetcher::new(config, cache.path().to_path_buf(), &FetchOptions::default())
+}
+
+/// A stand-in for a remote repository: an HTTP server on a free local port that answers
+/// every request with `respond(path, request_head)`'s status and body, one request per
+/// connection. Returns the server's base URL.
+pub async fn http_server<F>(respond: F) -> String
+where
+    F: Fn(&str, &str) -> (u16, Vec<u8>) + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let respond = Arc::new(respond);
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let respond = respond.clone();
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                let mut buf = [0; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => head.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let head = String::from_utf8_lossy(&head);
+                let path = head.split_whitespace().nth(1).unwrap_or("/");
+                let (status, body) = respond(path, &head);
+                let response = format!(
+                    "HTTP/1.1 {status} Status\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.write_all(&body).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+    url
+}
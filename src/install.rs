@@ -0,0 +1,144 @@
+use crate::config::{Config, DependencySpec, Scope};
+use crate::repository::maven_local_repository;
+use indexmap::IndexMap;
+use quick_xml::escape::escape;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// `<dependency>` entries for one dependency table, sorted for a stable POM.
+fn write_dependencies(
+    pom: &mut String,
+    dependencies: Option<&IndexMap<String, DependencySpec>>,
+    default_scope: Scope,
+) {
+    let mut dependencies: Vec<_> = dependencies.into_iter().flatten().collect();
+    dependencies.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (dep, spec) in dependencies {
+        let Some((group, artifact)) = dep.split_once(':') else {
+            continue;
+        };
+        let _ = writeln!(pom, "    <dependency>");
+        let _ = writeln!(pom, "      <groupId>{}</groupId>", escape(group));
+        let _ = writeln!(pom, "      <artifactId>{}</artifactId>", escape(artifact));
+        // Versionless declarations are managed by the imported platforms
+        if !spec.version().is_empty() {
+            let _ = writeln!(pom, "      <version>{}</version>", escape(spec.version()));
+        }
+        if let Some(dep_type) = spec.dep_type().filter(|t| *t != "jar") {
+            let _ = writeln!(pom, "      <type>{}</type>", escape(dep_type));
+        }
+        if let Some(classifier) = spec.classifier() {
+            let _ = writeln!(pom, "      <classifier>{}</classifier>", escape(classifier));
+        }
+        let scope = spec.scope(default_scope);
+        if scope != Scope::Compile {
+            let _ = writeln!(pom, "      <scope>{}</scope>", scope.as_str());
+        }
+        let exclusions: Vec<_> = spec
+            .exclude()
+            .iter()
+            .filter_map(|e| e.split_once(':'))
+            .collect();
+        if !exclusions.is_empty() {
+            let _ = writeln!(pom, "      <exclusions>");
+            for (group, artifact) in exclusions {
+                let _ = writeln!(pom, "        <exclusion>");
+                let _ = writeln!(pom, "          <groupId>{}</groupId>", escape(group));
+                let _ = writeln!(
+                    pom,
+                    "          <artifactId>{}</artifactId>",
+                    escape(artifact)
+                );
+                let _ = writeln!(pom, "        </exclusion>");
+            }
+            let _ = writeln!(pom, "      </exclusions>");
+        }
+        let _ = writeln!(pom, "    </dependency>");
+    }
+}
+
+/// POM describing the project for Maven and Gradle consumers: its coordinates, its
+/// platforms as imported BOMs and its declared dependencies.
+fn generate_pom(config: &Config, group: &str) -> String {
+    let project = &config.project;
+    let mut pom = String::new();
+    let _ = writeln!(pom, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        pom,
+        r#"<project xmlns="http://maven.apache.org/POM/4.0.0" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://maven.apache.org/POM/4.0.0 https://maven.apache.org/xsd/maven-4.0.0.xsd">"#
+    );
+    let _ = writeln!(pom, "  <modelVersion>4.0.0</modelVersion>");
+    let _ = writeln!(pom, "  <groupId>{}</groupId>", escape(group));
+    let _ = writeln!(pom, "  <artifactId>{}</artifactId>", escape(&project.name));
+    let _ = writeln!(pom, "  <version>{}</version>", escape(&project.version));
+    let _ = writeln!(pom, "  <packaging>jar</packaging>");
+
+    if let Some(platforms) = config.platforms.as_ref().filter(|p| !p.is_empty()) {
+        let mut platforms: Vec<_> = platforms.iter().collect();
+        platforms.sort();
+        let _ = writeln!(pom, "  <dependencyManagement>");
+        let _ = writeln!(pom, "    <dependencies>");
+        for (bom, version) in platforms {
+            let Some((group, artifact)) = bom.split_once(':') else {
+                continue;
+            };
+            let _ = writeln!(pom, "      <dependency>");
+            let _ = writeln!(pom, "        <groupId>{}</groupId>", escape(group));
+            let _ = writeln!(pom, "        <artifactId>{}</artifactId>", escape(artifact));
+            let _ = writeln!(pom, "        <version>{}</version>", escape(version));
+            let _ = writeln!(pom, "        <type>pom</type>");
+            let _ = writeln!(pom, "        <scope>import</scope>");
+            let _ = writeln!(pom, "      </dependency>");
+        }
+        let _ = writeln!(pom, "    </dependencies>");
+        let _ = writeln!(pom, "  </dependencyManagement>");
+    }
+
+    let _ = writeln!(pom, "  <dependencies>");
+    write_dependencies(&mut pom, config.dependencies.as_ref(), Scope::Compile);
+    write_dependencies(&mut pom, config.test_dependencies.as_ref(), Scope::Test);
+    let _ = writeln!(pom, "  </dependencies>");
+    let _ = writeln!(pom, "</project>");
+    pom
+}
+
+/// Package the project and publish the JAR and a generated POM to `~/.m2/repository`
+/// under `group/artifact/version/`, where Maven and Gradle builds on this machine find it.
+pub fn install_project(config: &Config) -> io::Result<()> {
+    let group = config.project.group.as_deref().ok_or_else(|| {
+        io::Error::other(
+            "`group` must be set in [project] to install to the local Maven repository",
+        )
+    })?;
+    let m2 = maven_local_repository()
+        .ok_or_else(|| io::Error::other("Cannot locate the home directory for ~/.m2"))?;
+
+    crate::package::package_project(config, false)?;
+
+    let project = &config.project;
+    let target_dir = m2
+        .join(group.replace('.', "/"))
+        .join(&project.name)
+        .join(&project.version);
+    fs::create_dir_all(&target_dir)?;
+
+    let base_name = format!("{}-{}", project.name, project.version);
+    let jar_name = format!("{base_name}.jar");
+    fs::copy(Path::new(&jar_name), target_dir.join(&jar_name))?;
+    fs::write(
+        target_dir.join(format!("{base_name}.pom")),
+        generate_pom(config, group),
+    )?;
+
+    println!(
+        "✓ Installed {}:{}:{} to {}",
+        group,
+        project.name,
+        project.version,
+        target_dir.display()
+    );
+    Ok(())
+}
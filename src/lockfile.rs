@@ -0,0 +1,149 @@
+use crate::config::Scope;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+pub const LOCK_FILE: &str = "rrrgradle.lock";
+
+const LOCK_HEADER: &str = "# This file is generated by rrrGradle. Do not edit it by hand.\n";
+
+/// One resolved artifact pinned in `rrrgradle.lock`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedArtifact {
+    /// `group:artifact:version[:classifier]`
+    pub id: String,
+    #[serde(rename = "type", default = "default_type")]
+    pub dep_type: String,
+    pub scope: Scope,
+    /// Chain of `group:artifact:version` that introduced it; empty for direct dependencies
+    #[serde(default)]
+    pub path: Vec<String>,
+    pub sha256: Option<String>,
+}
+
+fn default_type() -> String {
+    "jar".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockFile {
+    pub version: u32,
+    #[serde(default, rename = "artifact")]
+    pub artifacts: Vec<LockedArtifact>,
+}
+
+impl LockFile {
+    pub fn new(mut artifacts: Vec<LockedArtifact>) -> Self {
+        artifacts.sort_by(|a, b| a.id.cmp(&b.id).then(a.dep_type.cmp(&b.dep_type)));
+        LockFile {
+            version: 1,
+            artifacts,
+        }
+    }
+
+    /// Human-readable differences from `other` (`+` added, `-` removed, `~` changed).
+    pub fn diff(&self, other: &LockFile) -> Vec<String> {
+        let index = |lock: &LockFile| -> BTreeMap<(String, String), LockedArtifact> {
+            lock.artifacts
+                .iter()
+                .map(|a| ((a.id.clone(), a.dep_type.clone()), a.clone()))
+                .collect()
+        };
+        let (ours, theirs) = (index(self), index(other));
+
+        let mut changes = Vec::new();
+        for (key, artifact) in &theirs {
+            match ours.get(key) {
+                None => changes.push(format!("+ {}", artifact.id)),
+                Some(current) if current != artifact => changes.push(format!("~ {}", artifact.id)),
+                Some(_) => {}
+            }
+        }
+        for (key, artifact) in &ours {
+            if !theirs.contains_key(key) {
+                changes.push(format!("- {}", artifact.id));
+            }
+        }
+        changes
+    }
+}
+
+/// Read `rrrgradle.lock`, or `None` when the project has none yet.
+pub fn read_lock_file() -> Option<LockFile> {
+    let content = fs::read_to_string(LOCK_FILE).ok()?;
+    match toml::from_str(&content) {
+        Ok(lock) => Some(lock),
+        Err(e) => {
+            eprintln!("⚠️  Ignoring unreadable {}: {}", LOCK_FILE, e);
+            None
+        }
+    }
+}
+
+pub fn write_lock_file(lock: &LockFile) -> io::Result<()> {
+    let body = toml::to_string(lock).map_err(io::Error::other)?;
+    fs::write(LOCK_FILE, format!("{LOCK_HEADER}{body}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact(id: &str, scope: Scope, sha256: &str) -> LockedArtifact {
+        LockedArtifact {
+            id: id.to_string(),
+            dep_type: "jar".to_string(),
+            scope,
+            path: Vec::new(),
+            sha256: Some(sha256.to_string()),
+        }
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_artifacts() {
+        let locked = LockFile::new(vec![
+            artifact("com.example:kept:1.0", Scope::Compile, "aa"),
+            artifact("com.example:rescoped:1.0", Scope::Compile, "bb"),
+            artifact("com.example:rehashed:1.0", Scope::Compile, "cc"),
+            artifact("com.example:upgraded:1.0", Scope::Compile, "dd"),
+            artifact("com.example:dropped:1.0", Scope::Test, "ee"),
+        ]);
+        let resolved = LockFile::new(vec![
+            artifact("com.example:kept:1.0", Scope::Compile, "aa"),
+            artifact("com.example:rescoped:1.0", Scope::Runtime, "bb"),
+            artifact("com.example:rehashed:1.0", Scope::Compile, "ff"),
+            artifact("com.example:upgraded:1.1", Scope::Compile, "dd"),
+            artifact("com.example:added:1.0", Scope::Test, "00"),
+        ]);
+
+        // A new version is a new id, so it shows as one artifact removed and one added
+        assert_eq!(
+            locked.diff(&resolved),
+            [
+                "+ com.example:added:1.0",
+                "~ com.example:rehashed:1.0",
+                "~ com.example:rescoped:1.0",
+                "+ com.example:upgraded:1.1",
+                "- com.example:dropped:1.0",
+                "- com.example:upgraded:1.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_tells_types_of_one_id_apart() {
+        let jar = artifact("com.example:lib:1.0", Scope::Compile, "aa");
+        let pom = LockedArtifact {
+            dep_type: "pom".to_string(),
+            ..jar.clone()
+        };
+        let locked = LockFile::new(vec![jar.clone()]);
+
+        assert!(locked.diff(&LockFile::new(vec![jar])).is_empty());
+        assert_eq!(
+            locked.diff(&LockFile::new(vec![pom])),
+            ["+ com.example:lib:1.0", "- com.example:lib:1.0"]
+        );
+    }
+}
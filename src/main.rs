@@ -1,14 +1,29 @@
 mod build;
+mod checksum;
 mod cli;
 mod config;
+mod credentials;
+mod deps;
+mod edit;
 mod fetch;
+#[cfg(test)]
+mod fixtures;
+mod install;
+mod lockfile;
+mod metadata;
+mod module;
 mod package;
+mod paths;
 mod pom;
+mod profile;
+mod repository;
+mod resolve;
 mod run;
 mod test;
+mod version;
 
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, DepsCommand};
 use config::load_config;
 use std::fs;
 use std::io::Write;
@@ -24,6 +39,7 @@ async fn main() {
 
             let config = r#"
 [project]
+group = "com.example"
 name = "MyJavaApp"
 version = "0.1.0"
 main_class = "com.example.Main"
@@ -145,10 +161,24 @@ public class {}Test {{
             println!("Edit `rrrgradle.toml` to define your dependencies.");
         }
 
-        Commands::Fetch => {
+        Commands::Fetch {
+            locked,
+            sources,
+            javadoc,
+        } => {
             println!("Fetching dependencies...");
             let cfg = load_config();
-            fetch::fetch_dependencies(&cfg).await;
+            let options = fetch::FetchOptions {
+                locked,
+                offline: cli.offline,
+                refresh: cli.refresh_dependencies,
+                sources,
+                javadoc,
+            };
+            if let Err(e) = fetch::fetch_dependencies(&cfg, &options).await {
+                eprintln!("✗ Fetch failed: {}", e);
+                std::process::exit(1);
+            }
         }
 
         Commands::Build => {
@@ -188,5 +218,86 @@ public class {}Test {{
                 eprintln!("✗ Packaging failed: {}", e);
             }
         }
+
+        Commands::Install => {
+            println!("Installing project to the local Maven repository...");
+            let cfg = load_config();
+            if let Err(e) = install::install_project(&cfg) {
+                eprintln!("✗ Install failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Add {
+            dependency,
+            test,
+            scope,
+            fetch,
+        } => {
+            let cfg = load_config();
+            let options = fetch::FetchOptions {
+                offline: cli.offline,
+                refresh: cli.refresh_dependencies,
+                ..Default::default()
+            };
+            let scope = scope.and_then(|s| config::Scope::from_pom(Some(&s)));
+            if let Err(e) = edit::add_dependency(&cfg, &options, &dependency, test, scope).await {
+                eprintln!("✗ Add failed: {}", e);
+                std::process::exit(1);
+            }
+            if fetch {
+                println!("Fetching dependencies...");
+                if let Err(e) = fetch::fetch_dependencies(&load_config(), &options).await {
+                    eprintln!("✗ Fetch failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::Remove { dependency, test } => {
+            let options = fetch::FetchOptions {
+                offline: cli.offline,
+                refresh: cli.refresh_dependencies,
+                ..Default::default()
+            };
+            if let Err(e) = edit::remove_dependency(&options, &dependency, test).await {
+                eprintln!("✗ Remove failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Deps { command } => {
+            let cfg = load_config();
+            let options = fetch::FetchOptions {
+                offline: cli.offline,
+                refresh: cli.refresh_dependencies,
+                ..Default::default()
+            };
+            let result = match command {
+                DepsCommand::Tree { format } => deps::tree(&cfg, &options, format).await,
+                DepsCommand::Why { artifact } => deps::why(&cfg, &options, &artifact).await,
+                DepsCommand::Outdated { prereleases } => {
+                    deps::outdated(&cfg, &options, prereleases).await
+                }
+                DepsCommand::Update {
+                    patch,
+                    minor,
+                    prereleases,
+                } => {
+                    let level = if patch {
+                        deps::UpgradeLevel::Patch
+                    } else if minor {
+                        deps::UpgradeLevel::Minor
+                    } else {
+                        deps::UpgradeLevel::Major
+                    };
+                    deps::update(&cfg, &options, level, prereleases).await
+                }
+            };
+            if let Err(e) = result {
+                eprintln!("✗ Dependency inspection failed: {}", e);
+                std::process::exit(1);
+            }
+        }
     }
 }
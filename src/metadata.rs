@@ -0,0 +1,341 @@
+use crate::version::{ComparableVersion, is_prerelease};
+use quick_xml::Reader;
+use quick_xml::events::Event;
+
+/// Version keywords resolved against `maven-metadata.xml` instead of naming a release.
+const LATEST: [&str; 2] = ["LATEST", "latest.integration"];
+const RELEASE: [&str; 2] = ["RELEASE", "latest.release"];
+
+/// One `<snapshotVersion>`: the timestamped version of one file of a SNAPSHOT deploy.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotVersion {
+    pub classifier: Option<String>,
+    pub extension: String,
+    pub value: String,
+}
+
+/// The parts of a `maven-metadata.xml` the resolver uses, at either the artifact level
+/// (`group/artifact/`, listing versions) or the SNAPSHOT level
+/// (`group/artifact/1.0-SNAPSHOT/`, naming the timestamped build).
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    pub latest: Option<String>,
+    pub release: Option<String>,
+    pub versions: Vec<String>,
+    /// `<snapshot>` timestamp and build number of the newest deploy
+    pub snapshot: Option<(String, String)>,
+    pub snapshot_versions: Vec<SnapshotVersion>,
+    /// `yyyyMMddHHmmss`, so string order is chronological
+    pub last_updated: String,
+}
+
+impl Metadata {
+    /// Combine metadata for the same directory from several repositories: versions are
+    /// unioned and the most recently updated repository decides latest, release and snapshot.
+    pub fn merge(&mut self, other: Metadata) {
+        for version in &other.versions {
+            if !self.versions.contains(version) {
+                self.versions.push(version.clone());
+            }
+        }
+        if other.last_updated > self.last_updated {
+            let versions = std::mem::take(&mut self.versions);
+            *self = Metadata { versions, ..other };
+        }
+    }
+
+    /// Concrete version for `LATEST`/`RELEASE`, falling back to the highest listed version.
+    pub fn dynamic_version(&self, keyword: &str) -> Option<String> {
+        let highest = |include_snapshots: bool| {
+            self.versions
+                .iter()
+                .filter(|v| include_snapshots || !is_snapshot(v))
+                .max_by_key(|v| ComparableVersion::new(v))
+                .cloned()
+        };
+        if LATEST.contains(&keyword) {
+            self.latest.clone().or_else(|| highest(true))
+        } else {
+            self.release.clone().or_else(|| highest(false))
+        }
+    }
+
+    /// Highest listed version that is not a pre-release, falling back to `<release>`.
+    pub fn newest_release(&self) -> Option<String> {
+        self.versions
+            .iter()
+            .filter(|v| !is_prerelease(v))
+            .max_by_key(|v| ComparableVersion::new(v))
+            .cloned()
+            .or_else(|| self.release.clone())
+    }
+
+    /// Timestamped version of a SNAPSHOT `base_version`'s files, e.g.
+    /// `1.0-20240115.093000-7` for `1.0-SNAPSHOT`. Prefers the POM's own
+    /// `<snapshotVersion>` so the POM and the artifacts of one deploy match.
+    pub fn snapshot_version(&self, base_version: &str) -> Option<String> {
+        if let Some(pom) = self
+            .snapshot_versions
+            .iter()
+            .find(|s| s.extension == "pom" && s.classifier.is_none())
+        {
+            return Some(pom.value.clone());
+        }
+        let (timestamp, build_number) = self.snapshot.as_ref()?;
+        let prefix = base_version.strip_suffix("-SNAPSHOT")?;
+        Some(format!("{prefix}-{timestamp}-{build_number}"))
+    }
+}
+
+/// `LATEST`, `RELEASE` and their Gradle spellings.
+pub fn is_dynamic(version: &str) -> bool {
+    LATEST.contains(&version) || RELEASE.contains(&version)
+}
+
+pub fn is_snapshot(version: &str) -> bool {
+    version.ends_with("-SNAPSHOT")
+}
+
+/// Directory version of a possibly timestamped SNAPSHOT: `1.0-20240115.093000-7` lives
+/// under `1.0-SNAPSHOT/`. Other versions are returned unchanged.
+pub fn base_version(version: &str) -> String {
+    let mut parts = version.rsplitn(3, '-');
+    if let (Some(build), Some(timestamp), Some(prefix)) = (parts.next(), parts.next(), parts.next())
+    {
+        let is_timestamp = timestamp.len() == 15
+            && timestamp.as_bytes()[8] == b'.'
+            && timestamp
+                .chars()
+                .enumerate()
+                .all(|(i, c)| i == 8 || c.is_ascii_digit());
+        if is_timestamp && !build.is_empty() && build.chars().all(|c| c.is_ascii_digit()) {
+            return format!("{prefix}-SNAPSHOT");
+        }
+    }
+    version.to_string()
+}
+
+pub fn parse_metadata(xml: &str) -> Option<Metadata> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    let mut metadata = Metadata::default();
+    let mut path: Vec<String> = Vec::new();
+    let mut timestamp = None;
+    let mut build_number = None;
+    let mut snapshot_version = SnapshotVersion::default();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                if tag == "snapshotVersion" {
+                    snapshot_version = SnapshotVersion::default();
+                }
+                path.push(tag);
+            }
+            Ok(Event::End(_)) => {
+                let closed = path.pop();
+                if closed.as_deref() == Some("snapshotVersion")
+                    && !snapshot_version.value.is_empty()
+                {
+                    metadata
+                        .snapshot_versions
+                        .push(std::mem::take(&mut snapshot_version));
+                }
+            }
+            Ok(Event::Text(e)) => {
+                let text = e.unescape().ok()?.to_string();
+                let parents: Vec<&str> = path.iter().map(String::as_str).collect();
+                match parents.as_slice() {
+                    [.., "versioning", "latest"] => metadata.latest = Some(text),
+                    [.., "versioning", "release"] => metadata.release = Some(text),
+                    [.., "versioning", "lastUpdated"] => metadata.last_updated = text,
+                    [.., "versions", "version"] => metadata.versions.push(text),
+                    [.., "snapshot", "timestamp"] => timestamp = Some(text),
+                    [.., "snapshot", "buildNumber"] => build_number = Some(text),
+                    [.., "snapshotVersion", "classifier"] => {
+                        snapshot_version.classifier = Some(text)
+                    }
+                    [.., "snapshotVersion", "extension"] => snapshot_version.extension = text,
+                    [.., "snapshotVersion", "value"] => snapshot_version.value = text,
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => return None,
+            _ => {}
+        }
+    }
+
+    metadata.snapshot = timestamp.zip(build_number);
+    Some(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTIFACT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata>
+  <groupId>com.example</groupId>
+  <artifactId>lib</artifactId>
+  <versioning>
+    <latest>2.1-SNAPSHOT</latest>
+    <release>2.0</release>
+    <versions>
+      <version>1.0</version>
+      <version>2.0</version>
+      <version>2.1-SNAPSHOT</version>
+    </versions>
+    <lastUpdated>20240115093000</lastUpdated>
+  </versioning>
+</metadata>"#;
+
+    const SNAPSHOT: &str = r#"<metadata>
+  <version>2.1-SNAPSHOT</version>
+  <versioning>
+    <snapshot>
+      <timestamp>20240115.093000</timestamp>
+      <buildNumber>7</buildNumber>
+    </snapshot>
+    <lastUpdated>20240115093000</lastUpdated>
+    <snapshotVersions>
+      <snapshotVersion>
+        <classifier>sources</classifier>
+        <extension>jar</extension>
+        <value>2.1-20240115.093000-6</value>
+      </snapshotVersion>
+      <snapshotVersion>
+        <extension>pom</extension>
+        <value>2.1-20240115.092000-5</value>
+      </snapshotVersion>
+    </snapshotVersions>
+  </versioning>
+</metadata>"#;
+
+    fn versions(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_artifact_metadata() {
+        let metadata = parse_metadata(ARTIFACT).unwrap();
+        assert_eq!(metadata.latest.as_deref(), Some("2.1-SNAPSHOT"));
+        assert_eq!(metadata.release.as_deref(), Some("2.0"));
+        assert_eq!(metadata.versions, versions(&["1.0", "2.0", "2.1-SNAPSHOT"]));
+        assert_eq!(metadata.last_updated, "20240115093000");
+        assert!(metadata.snapshot.is_none());
+    }
+
+    #[test]
+    fn parses_snapshot_metadata() {
+        let metadata = parse_metadata(SNAPSHOT).unwrap();
+        assert_eq!(
+            metadata.snapshot,
+            Some(("20240115.093000".to_string(), "7".to_string()))
+        );
+        assert_eq!(metadata.snapshot_versions.len(), 2);
+        assert_eq!(
+            metadata.snapshot_versions[0].classifier.as_deref(),
+            Some("sources")
+        );
+        assert_eq!(metadata.snapshot_versions[1].extension, "pom");
+    }
+
+    #[test]
+    fn malformed_metadata_is_rejected() {
+        assert!(parse_metadata("<metadata><versioning></metadata>").is_none());
+    }
+
+    #[test]
+    fn snapshot_version_prefers_the_poms_own_entry() {
+        let metadata = parse_metadata(SNAPSHOT).unwrap();
+        assert_eq!(
+            metadata.snapshot_version("2.1-SNAPSHOT").as_deref(),
+            Some("2.1-20240115.092000-5")
+        );
+    }
+
+    #[test]
+    fn snapshot_version_falls_back_to_the_newest_deploy() {
+        let mut metadata = parse_metadata(SNAPSHOT).unwrap();
+        metadata.snapshot_versions.clear();
+        assert_eq!(
+            metadata.snapshot_version("2.1-SNAPSHOT").as_deref(),
+            Some("2.1-20240115.093000-7")
+        );
+        assert_eq!(metadata.snapshot_version("2.1"), None);
+        assert_eq!(Metadata::default().snapshot_version("2.1-SNAPSHOT"), None);
+    }
+
+    #[test]
+    fn base_version_of_timestamped_snapshots() {
+        for (version, base) in [
+            ("1.0-20240115.093000-7", "1.0-SNAPSHOT"),
+            ("1.0-alpha-20240115.093000-12", "1.0-alpha-SNAPSHOT"),
+            ("1.0-SNAPSHOT", "1.0-SNAPSHOT"),
+            ("1.0", "1.0"),
+            ("1.0-alpha-1", "1.0-alpha-1"),
+            ("1.0-20240115-7", "1.0-20240115-7"),
+            ("1.0-20240115.093000-", "1.0-20240115.093000-"),
+        ] {
+            assert_eq!(base_version(version), base, "{version}");
+        }
+    }
+
+    #[test]
+    fn dynamic_versions_use_latest_and_release() {
+        let metadata = parse_metadata(ARTIFACT).unwrap();
+        for keyword in LATEST {
+            assert_eq!(
+                metadata.dynamic_version(keyword).as_deref(),
+                Some("2.1-SNAPSHOT")
+            );
+        }
+        for keyword in RELEASE {
+            assert_eq!(metadata.dynamic_version(keyword).as_deref(), Some("2.0"));
+        }
+    }
+
+    #[test]
+    fn dynamic_versions_fall_back_to_the_highest_listed() {
+        let metadata = Metadata {
+            versions: versions(&["1.10", "1.9", "2.0-SNAPSHOT"]),
+            ..Default::default()
+        };
+        assert_eq!(
+            metadata.dynamic_version("LATEST").as_deref(),
+            Some("2.0-SNAPSHOT")
+        );
+        assert_eq!(metadata.dynamic_version("RELEASE").as_deref(), Some("1.10"));
+    }
+
+    #[test]
+    fn merge_unions_versions_and_takes_the_rest_from_the_newest() {
+        let mut merged = Metadata {
+            release: Some("1.0".to_string()),
+            versions: versions(&["0.9", "1.0"]),
+            last_updated: "20240101000000".to_string(),
+            ..Default::default()
+        };
+        merged.merge(Metadata {
+            release: Some("1.1".to_string()),
+            versions: versions(&["1.0", "1.1"]),
+            last_updated: "20240201000000".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(merged.versions, versions(&["0.9", "1.0", "1.1"]));
+        assert_eq!(merged.release.as_deref(), Some("1.1"));
+        assert_eq!(merged.last_updated, "20240201000000");
+
+        merged.merge(Metadata {
+            release: Some("0.8".to_string()),
+            versions: versions(&["0.8"]),
+            last_updated: "20230101000000".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(merged.versions, versions(&["0.9", "1.0", "1.1", "0.8"]));
+        assert_eq!(merged.release.as_deref(), Some("1.1"));
+    }
+}
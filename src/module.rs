@@ -0,0 +1,300 @@
+use crate::pom::{Exclusion, PomDependency};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const USAGE: &str = "org.gradle.usage";
+const CATEGORY: &str = "org.gradle.category";
+const JVM_VERSION: &str = "org.gradle.jvm.version";
+const JVM_ENVIRONMENT: &str = "org.gradle.jvm.environment";
+const LIBRARY_ELEMENTS: &str = "org.gradle.libraryelements";
+const BUNDLING: &str = "org.gradle.dependency.bundling";
+const KOTLIN_PLATFORM: &str = "org.jetbrains.kotlin.platform.type";
+
+/// A Gradle Module Metadata (`.module`) file: the component's variants, each with its
+/// own attributes and dependencies.
+#[derive(Debug, Deserialize)]
+pub struct ModuleMetadata {
+    #[serde(default)]
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Variant {
+    #[serde(default)]
+    pub attributes: HashMap<String, Value>,
+    #[serde(default)]
+    pub dependencies: Vec<ModuleDependency>,
+    #[serde(default, rename = "dependencyConstraints")]
+    pub dependency_constraints: Vec<ModuleDependency>,
+    /// The variant is published by another module, e.g. a Kotlin Multiplatform root
+    /// pointing at its `-jvm` module
+    #[serde(rename = "available-at")]
+    pub available_at: Option<ModuleRef>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModuleRef {
+    pub group: String,
+    pub module: String,
+    pub version: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModuleDependency {
+    pub group: String,
+    pub module: String,
+    pub version: Option<VersionConstraint>,
+    #[serde(default)]
+    pub excludes: Vec<ModuleExclude>,
+    #[serde(default)]
+    pub attributes: HashMap<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VersionConstraint {
+    pub strictly: Option<String>,
+    pub requires: Option<String>,
+    pub prefers: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModuleExclude {
+    pub group: String,
+    pub module: String,
+}
+
+/// Which side of a library a consumer needs, like Maven's compile and runtime scopes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Usage {
+    Api,
+    Runtime,
+}
+
+/// What the variant-aware resolver found for a module: its dependencies expressed as
+/// POM dependencies, or the module that actually publishes the selected variant.
+pub enum ModuleResolution {
+    Dependencies {
+        dependencies: Vec<PomDependency>,
+        /// Platforms the variant depends on, as BOM imports that supply managed versions
+        platforms: Vec<PomDependency>,
+    },
+    AvailableAt(ModuleRef),
+}
+
+fn attribute(variant: &Variant, name: &str) -> Option<String> {
+    match variant.attributes.get(name)? {
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+impl ModuleDependency {
+    fn version(&self) -> String {
+        self.version
+            .as_ref()
+            .and_then(|v| {
+                v.strictly
+                    .clone()
+                    .or_else(|| v.requires.clone())
+                    .or_else(|| v.prefers.clone())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Dependency on a platform (Gradle's BOM): contributes constraints, not a JAR
+    fn is_platform(&self) -> bool {
+        matches!(
+            self.attributes.get(CATEGORY).and_then(Value::as_str),
+            Some("platform" | "enforced-platform")
+        )
+    }
+}
+
+impl Variant {
+    /// Whether a JVM library consumer asking for `usage` on `java_version` can use this
+    /// variant at all.
+    fn is_compatible(&self, usage: Usage, java_version: Option<u32>) -> bool {
+        let usage_matches = attribute(self, USAGE).is_some_and(|u| match usage {
+            Usage::Api => u.ends_with("-api"),
+            Usage::Runtime => u.ends_with("-runtime"),
+        });
+        let is_library = attribute(self, CATEGORY).is_none_or(|c| c == "library");
+        let runs_on_jvm = match (java_version, self.jvm_version()) {
+            (Some(target), Some(required)) => required <= target,
+            _ => true,
+        };
+        let jvm_platform = attribute(self, KOTLIN_PLATFORM).is_none_or(|p| p == "jvm");
+        usage_matches && is_library && runs_on_jvm && jvm_platform
+    }
+
+    fn jvm_version(&self) -> Option<u32> {
+        attribute(self, JVM_VERSION)?.parse().ok()
+    }
+
+    /// Preference among compatible variants: a standard JVM over Android, packaged JARs
+    /// over class directories, external dependencies over shaded ones, then the newest
+    /// JVM the project can run.
+    fn score(&self) -> (bool, bool, bool, u32) {
+        (
+            attribute(self, JVM_ENVIRONMENT).is_none_or(|e| e == "standard-jvm"),
+            attribute(self, LIBRARY_ELEMENTS).is_none_or(|e| e == "jar"),
+            attribute(self, BUNDLING).is_none_or(|b| b == "external"),
+            self.jvm_version().unwrap_or(0),
+        )
+    }
+}
+
+impl ModuleMetadata {
+    /// The best variant for `usage`; an API consumer falls back to the runtime variant
+    /// when the module publishes no API one.
+    pub fn select(&self, usage: Usage, java_version: Option<u32>) -> Option<&Variant> {
+        let best = |usage| {
+            self.variants
+                .iter()
+                .filter(|v| v.is_compatible(usage, java_version))
+                .max_by_key(|v| v.score())
+        };
+        match usage {
+            Usage::Api => best(Usage::Api).or_else(|| best(Usage::Runtime)),
+            Usage::Runtime => best(Usage::Runtime),
+        }
+    }
+
+    /// Dependencies of the variants a JVM consumer would pick, mapped onto Maven scopes:
+    /// API dependencies become `compile`, runtime-only ones `runtime`. `None` when no
+    /// variant fits, so the caller falls back to the POM.
+    pub fn resolve(&self, java_version: Option<u32>) -> Option<ModuleResolution> {
+        let runtime = self.select(Usage::Runtime, java_version)?;
+        let api = self.select(Usage::Api, java_version);
+        if let Some(target) = runtime.available_at.as_ref() {
+            return Some(ModuleResolution::AvailableAt(target.clone()));
+        }
+
+        let constraints: HashMap<(&str, &str), String> = runtime
+            .dependency_constraints
+            .iter()
+            .map(|c| ((c.group.as_str(), c.module.as_str()), c.version()))
+            .collect();
+        let in_api = |dep: &ModuleDependency| {
+            api.is_some_and(|api| {
+                api.dependencies
+                    .iter()
+                    .any(|d| d.group == dep.group && d.module == dep.module)
+            })
+        };
+
+        let (platforms, dependencies): (Vec<_>, Vec<_>) = runtime
+            .dependencies
+            .iter()
+            .partition(|dep| dep.is_platform());
+        let platforms = platforms
+            .into_iter()
+            .map(|dep| PomDependency {
+                group_id: dep.group.clone(),
+                artifact_id: dep.module.clone(),
+                version: dep.version(),
+                scope: Some("import".to_string()),
+                dep_type: Some("pom".to_string()),
+                ..Default::default()
+            })
+            .collect();
+        let dependencies = dependencies
+            .into_iter()
+            .map(|dep| {
+                let mut version = dep.version();
+                if version.is_empty()
+                    && let Some(constrained) =
+                        constraints.get(&(dep.group.as_str(), dep.module.as_str()))
+                {
+                    version = constrained.clone();
+                }
+                PomDependency {
+                    group_id: dep.group.clone(),
+                    artifact_id: dep.module.clone(),
+                    version,
+                    scope: Some(if in_api(dep) { "compile" } else { "runtime" }.to_string()),
+                    exclusions: dep
+                        .excludes
+                        .iter()
+                        .map(|e| Exclusion {
+                            group_id: e.group.clone(),
+                            artifact_id: e.module.clone(),
+                        })
+                        .collect(),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        Some(ModuleResolution::Dependencies {
+            dependencies,
+            platforms,
+        })
+    }
+}
+
+/// Parse a `.module` file, or `None` (with a warning) when it is not valid JSON.
+pub fn parse_module(path: &Path) -> Option<ModuleMetadata> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(module) => Some(module),
+        Err(e) => {
+            eprintln!("⚠️  Ignoring unreadable {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module(json: &str) -> ModuleMetadata {
+        serde_json::from_str(json).expect("valid module JSON")
+    }
+
+    #[test]
+    fn platforms_become_imports_and_constraints_fill_versions() {
+        let metadata = module(
+            r#"{"variants": [{
+                "attributes": {"org.gradle.usage": "java-runtime", "org.gradle.category": "library"},
+                "dependencies": [
+                    {"group": "com.example", "module": "bom", "version": {"requires": "1.2"},
+                     "attributes": {"org.gradle.category": "platform"}},
+                    {"group": "com.example", "module": "core"},
+                    {"group": "com.example", "module": "util"}
+                ],
+                "dependencyConstraints": [
+                    {"group": "com.example", "module": "util", "version": {"strictly": "2.0"}}
+                ]
+            }]}"#,
+        );
+        let Some(ModuleResolution::Dependencies {
+            dependencies,
+            platforms,
+        }) = metadata.resolve(Some(17))
+        else {
+            panic!("expected dependencies");
+        };
+
+        assert_eq!(platforms.len(), 1);
+        assert_eq!(platforms[0].key(), "com.example:bom");
+        assert_eq!(platforms[0].version, "1.2");
+        assert_eq!(platforms[0].scope.as_deref(), Some("import"));
+        assert_eq!(platforms[0].dep_type.as_deref(), Some("pom"));
+
+        let versions: Vec<_> = dependencies
+            .iter()
+            .map(|d| (d.key(), d.version.as_str()))
+            .collect();
+        assert_eq!(
+            versions,
+            [
+                ("com.example:core".to_string(), ""),
+                ("com.example:util".to_string(), "2.0")
+            ]
+        );
+    }
+}
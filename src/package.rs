@@ -1,4 +1,7 @@
 use crate::config::{Config, SourceSet};
+use crate::paths::cache_root;
+use crate::resolve::{Classpath, load_classpath};
+use std::collections::HashSet;
 use std::fs::{self, File};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
@@ -29,6 +32,27 @@ fn copy_resources(source_set: &SourceSet, target_dir: &Path) -> io::Result<()> {
     Ok(())
 }
 
+/// File name of a dependency JAR under `lib/`. JAR names only carry the artifactId, so
+/// when two groups publish the same one, the later JAR is prefixed with its groupId (taken
+/// from its `group/artifact/version/` location in the cache).
+fn lib_name(jar: &Path, taken: &mut HashSet<String>) -> String {
+    let jar_name = jar.file_name().unwrap().to_string_lossy().to_string();
+    if taken.insert(jar_name.clone()) {
+        return jar_name;
+    }
+    let root = cache_root();
+    let dirs: Vec<_> = jar
+        .strip_prefix(&root)
+        .unwrap_or(jar)
+        .parent()
+        .map(|p| p.iter().map(|c| c.to_string_lossy().to_string()).collect())
+        .unwrap_or_default();
+    let group = dirs[..dirs.len().saturating_sub(2)].join(".");
+    let jar_name = format!("{group}-{jar_name}");
+    taken.insert(jar_name.clone());
+    jar_name
+}
+
 pub fn package_project(config: &Config, uber: bool) -> io::Result<()> {
     // First, ensure the project is built
     if !crate::build::build_project(config) {
@@ -70,21 +94,18 @@ pub fn package_project(config: &Config, uber: bool) -> io::Result<()> {
         let lib_dir = temp_dir.join("lib");
         fs::create_dir_all(&lib_dir)?;
 
-        // Copy all dependency JARs to lib/
-        let deps: Vec<String> = if let Ok(entries) = fs::read_dir(".rrrgradle/cache") {
-            entries
-                .filter_map(|e| e.ok())
-                .filter(|e| e.path().extension().map_or(false, |ext| ext == "jar"))
-                .map(|e| {
-                    let jar_name = e.path().file_name().unwrap().to_string_lossy().to_string();
-                    let target = lib_dir.join(&jar_name);
-                    fs::copy(e.path(), &target)?;
-                    Ok(format!("lib/{}", jar_name))
-                })
-                .collect::<io::Result<_>>()?
-        } else {
-            vec![]
-        };
+        // Copy the resolved main dependency JARs to lib/
+        let mut taken = HashSet::new();
+        let deps: Vec<String> = load_classpath(Classpath::Runtime)
+            .iter()
+            .map(|jar| {
+                let jar = Path::new(jar);
+                let jar_name = lib_name(jar, &mut taken);
+                let target = lib_dir.join(&jar_name);
+                fs::copy(jar, &target)?;
+                Ok(format!("lib/{}", jar_name))
+            })
+            .collect::<io::Result<_>>()?;
 
         // Write Class-Path to manifest if we have dependencies
         if !deps.is_empty() {
@@ -0,0 +1,25 @@
+use std::env;
+use std::path::PathBuf;
+
+/// User-level rrrGradle directory: `$RRRGRADLE_HOME`, or `~/.rrrgradle`.
+pub fn rrrgradle_home() -> Option<PathBuf> {
+    match env::var_os("RRRGRADLE_HOME") {
+        Some(home) if !home.is_empty() => Some(PathBuf::from(home)),
+        _ => env::home_dir().map(|home| home.join(".rrrgradle")),
+    }
+}
+
+/// Artifact cache shared by every project on the machine, laid out like a Maven
+/// repository (`group/artifact/version/`). `$RRRGRADLE_HOME/cache` when set, otherwise
+/// `$XDG_CACHE_HOME/rrrgradle`, otherwise `~/.rrrgradle/cache`.
+pub fn cache_root() -> PathBuf {
+    if let Some(home) = env::var_os("RRRGRADLE_HOME").filter(|h| !h.is_empty()) {
+        return PathBuf::from(home).join("cache");
+    }
+    if let Some(xdg) = env::var_os("XDG_CACHE_HOME").filter(|x| !x.is_empty()) {
+        return PathBuf::from(xdg).join("rrrgradle");
+    }
+    rrrgradle_home()
+        .map(|home| home.join("cache"))
+        .unwrap_or_else(|| PathBuf::from(".rrrgradle/cache"))
+}
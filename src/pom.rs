@@ -1,29 +1,255 @@
+use crate::profile::{ActivationContext, OsCondition, Profile, active_profiles};
 use quick_xml::Reader;
 use quick_xml::events::Event;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufReader;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Error)]
+pub enum PomError {
+    #[error("cannot read POM {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("malformed POM {} at /{element}: {source}", path.display())]
+    Xml {
+        path: PathBuf,
+        element: String,
+        source: quick_xml::Error,
+    },
+    #[error("POM {} ends inside /{element}", path.display())]
+    Truncated { path: PathBuf, element: String },
+    #[error("{} is not a POM: expected a <project> with an <artifactId>", path.display())]
+    NotAPom { path: PathBuf },
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct PomDependency {
     pub group_id: String,
     pub artifact_id: String,
+    /// Empty when the version is expected to come from `<dependencyManagement>`
     pub version: String,
     pub scope: Option<String>,
+    pub dep_type: Option<String>,
+    pub classifier: Option<String>,
     pub optional: bool,
+    pub exclusions: Vec<Exclusion>,
+}
+
+/// A `group:artifact` pattern pruned from a dependency's subtree; `*` matches anything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Exclusion {
+    pub group_id: String,
+    pub artifact_id: String,
+}
+
+impl Exclusion {
+    /// Parse `group:artifact`; a bare group excludes every artifact in it.
+    pub fn parse(pattern: &str) -> Exclusion {
+        let (group_id, artifact_id) = pattern.split_once(':').unwrap_or((pattern, "*"));
+        Exclusion {
+            group_id: group_id.to_string(),
+            artifact_id: artifact_id.to_string(),
+        }
+    }
+
+    pub fn matches(&self, group_id: &str, artifact_id: &str) -> bool {
+        (self.group_id == "*" || self.group_id == group_id)
+            && (self.artifact_id == "*" || self.artifact_id == artifact_id)
+    }
+}
+
+impl PomDependency {
+    /// `group:artifact` key used for management and mediation
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.group_id, self.artifact_id)
+    }
+
+    /// True for a `<scope>import</scope>` BOM entry in `<dependencyManagement>`
+    pub fn is_bom_import(&self) -> bool {
+        self.scope.as_deref() == Some("import") && self.dep_type.as_deref() == Some("pom")
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct PomModel {
     pub group_id: Option<String>,
     pub artifact_id: String,
     pub version: Option<String>,
     pub properties: HashMap<String, String>,
     pub dependencies: Vec<PomDependency>,
+    pub dependency_management: Vec<PomDependency>,
     pub parent: Option<ParentPom>,
+    /// The POM carries Gradle's `published-with-gradle-metadata` marker, so a `.module`
+    /// file with variants is published next to it
+    pub gradle_metadata: bool,
+    /// `<distributionManagement><relocation>`: the artifact moved to new coordinates
+    pub relocation: Option<Relocation>,
+    /// `<profiles>`, kept apart until [`PomModel::activate_profiles`] merges the active ones
+    pub profiles: Vec<Profile>,
+    /// `<repositories>` the POM's own dependencies may be found in
+    pub repositories: Vec<PomRepository>,
+}
+
+/// A `<repository>` declared in a POM's `<repositories>`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PomRepository {
+    pub id: String,
+    pub url: String,
+}
+
+/// Add `repository` unless one with the same URL is already listed.
+pub fn add_repository(repositories: &mut Vec<PomRepository>, repository: PomRepository) {
+    let url = repository.url.trim_end_matches('/');
+    if !repositories
+        .iter()
+        .any(|r| r.url.trim_end_matches('/') == url)
+    {
+        repositories.push(repository);
+    }
+}
+
+/// New coordinates of a moved artifact; missing parts stay what they were.
+#[derive(Debug, Clone, Default)]
+pub struct Relocation {
+    pub group_id: Option<String>,
+    pub artifact_id: Option<String>,
+    pub version: Option<String>,
+    pub message: Option<String>,
+}
+
+impl Relocation {
+    /// `(group:artifact, version)` that `dep:version` was relocated to.
+    pub fn target(&self, dep: &str, version: &str) -> (String, String) {
+        let (group_id, artifact_id) = dep.split_once(':').unwrap_or((dep, ""));
+        (
+            format!(
+                "{}:{}",
+                self.group_id.as_deref().unwrap_or(group_id),
+                self.artifact_id.as_deref().unwrap_or(artifact_id)
+            ),
+            self.version.clone().unwrap_or_else(|| version.to_string()),
+        )
+    }
+}
+
+impl PomModel {
+    /// Merge the properties, dependencies and managed dependencies of the profiles that
+    /// are active in `context` into the model; a profile's declarations win over the
+    /// POM's own.
+    pub fn activate_profiles(&mut self, context: &ActivationContext) {
+        for profile in active_profiles(std::mem::take(&mut self.profiles), context) {
+            self.properties.extend(profile.properties);
+            for repository in profile.repositories {
+                add_repository(&mut self.repositories, repository);
+            }
+            for dep in profile.dependencies {
+                self.dependencies.retain(|d| d.key() != dep.key());
+                self.dependencies.push(dep);
+            }
+            for dep in profile.dependency_management {
+                self.dependency_management.retain(|d| d.key() != dep.key());
+                self.dependency_management.push(dep);
+            }
+        }
+    }
+
+    /// Fill in versionless dependencies from a managed set, dropping any that stay unversioned.
+    pub fn apply_dependency_management(&mut self, managed: &HashMap<String, PomDependency>) {
+        self.dependencies.retain_mut(|dep| {
+            if let Some(m) = managed.get(&dep.key()) {
+                if dep.version.is_empty() {
+                    dep.version = m.version.clone();
+                }
+                if dep.scope.is_none() {
+                    dep.scope = m.scope.clone();
+                }
+            }
+            !dep.version.is_empty()
+        });
+    }
+
+    /// Merge an (already effective) parent model into this one: inherited coordinates,
+    /// properties, managed dependencies and dependencies, with this model's own
+    /// declarations taking precedence.
+    pub fn inherit_from(&mut self, parent: &PomModel) {
+        if self.group_id.is_none() {
+            self.group_id = parent.group_id.clone();
+        }
+        if self.version.is_none() {
+            self.version = parent.version.clone();
+        }
+        for (k, v) in &parent.properties {
+            self.properties
+                .entry(k.clone())
+                .or_insert_with(|| v.clone());
+        }
+        self.dependency_management
+            .extend(parent.dependency_management.iter().cloned());
+        for dep in &parent.dependencies {
+            if !self.dependencies.iter().any(|d| d.key() == dep.key()) {
+                self.dependencies.push(dep.clone());
+            }
+        }
+        for repository in &parent.repositories {
+            add_repository(&mut self.repositories, repository.clone());
+        }
+    }
+
+    /// Resolve `${...}` placeholders in dependency coordinates against the model's
+    /// properties and the `project.*` built-ins.
+    pub fn interpolate(&mut self) {
+        let mut props = self.properties.clone();
+        let mut builtin = |key: &str, value: Option<&String>| {
+            if let Some(value) = value {
+                props.insert(format!("project.{key}"), value.clone());
+                props.insert(format!("pom.{key}"), value.clone());
+            }
+        };
+        builtin("groupId", self.group_id.as_ref());
+        builtin("artifactId", Some(&self.artifact_id));
+        builtin("version", self.version.as_ref());
+        if let Some(parent) = &self.parent {
+            builtin("parent.groupId", Some(&parent.group_id));
+            builtin("parent.artifactId", Some(&parent.artifact_id));
+            builtin("parent.version", Some(&parent.version));
+        }
+
+        for dep in self
+            .dependencies
+            .iter_mut()
+            .chain(self.dependency_management.iter_mut())
+        {
+            dep.group_id = resolve_placeholders(&dep.group_id, &props);
+            dep.artifact_id = resolve_placeholders(&dep.artifact_id, &props);
+            dep.version = resolve_placeholders(&dep.version, &props);
+            if let Some(classifier) = &dep.classifier {
+                dep.classifier = Some(resolve_placeholders(classifier, &props));
+            }
+            for exclusion in &mut dep.exclusions {
+                exclusion.group_id = resolve_placeholders(&exclusion.group_id, &props);
+                exclusion.artifact_id = resolve_placeholders(&exclusion.artifact_id, &props);
+            }
+        }
+        for repository in &mut self.repositories {
+            repository.url = resolve_placeholders(&repository.url, &props);
+        }
+        if let Some(relocation) = &mut self.relocation {
+            for part in [
+                &mut relocation.group_id,
+                &mut relocation.artifact_id,
+                &mut relocation.version,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                *part = resolve_placeholders(part, &props);
+            }
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ParentPom {
     pub group_id: String,
     pub artifact_id: String,
@@ -33,169 +259,513 @@ pub struct ParentPom {
 /// Resolves placeholders like ${...} using a properties map
 fn resolve_placeholders(s: &str, props: &HashMap<String, String>) -> String {
     let mut result = s.to_string();
-    let mut changed = true;
 
-    while changed {
-        changed = false;
+    // Bounded so self-referencing properties cannot loop forever
+    for _ in 0..16 {
         let mut replaced = result.clone();
         for (k, v) in props {
             let pattern = format!("${{{}}}", k);
             if replaced.contains(&pattern) {
                 replaced = replaced.replace(&pattern, v);
-                changed = true;
             }
         }
+        if replaced == result {
+            break;
+        }
         result = replaced;
     }
 
     result
 }
 
-pub fn parse_pom_model(path: &str) -> PomModel {
-    let file = File::open(path).expect("Failed to open POM file");
-    let file = BufReader::new(file);
-    let mut reader = Reader::from_reader(file);
-    reader.trim_text(true);
-
-    let mut buf = Vec::new();
-    let mut current_tag = String::new();
-
-    let mut dependencies = Vec::new();
-    let mut properties = HashMap::new();
-    let mut parent = ParentPom {
-        group_id: String::new(),
-        artifact_id: String::new(),
-        version: String::new(),
-    };
-
-    let mut current_dep = PomDependency {
-        group_id: String::new(),
-        artifact_id: String::new(),
-        version: String::new(),
-        scope: None,
-        optional: false,
-    };
+/// Parse state: the model read so far and the dependency and exclusion being read.
+/// Paths are the names of the open elements below `<project>`.
+#[derive(Default)]
+struct PomParser {
+    model: PomModel,
+    dependency: PomDependency,
+    exclusion: Exclusion,
+    repository: PomRepository,
+}
 
-    let mut artifact_id = String::new();
-    let mut group_id = None;
-    let mut version = None;
+/// Split a path inside `<profiles><profile>`, whose content is laid out like the
+/// project's, into whether it is in a profile and the path within.
+fn content_path<'a, 'b>(path: &'a [&'b str]) -> (bool, &'a [&'b str]) {
+    match path {
+        ["profiles", "profile", rest @ ..] => (true, rest),
+        _ => (false, path),
+    }
+}
 
-    let mut in_dependency = false;
-    let mut in_properties = false;
-    let mut in_parent = false;
+/// For a path inside a `<dependency>` of `<dependencies>` or `<dependencyManagement>`:
+/// whether it is managed, and the path within the dependency.
+fn dependency_path<'a, 'b>(path: &'a [&'b str]) -> Option<(bool, &'a [&'b str])> {
+    match path {
+        ["dependencies", "dependency", rest @ ..] => Some((false, rest)),
+        [
+            "dependencyManagement",
+            "dependencies",
+            "dependency",
+            rest @ ..,
+        ] => Some((true, rest)),
+        _ => None,
+    }
+}
 
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => {
-                let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                match tag.as_str() {
-                    "dependency" => {
-                        in_dependency = true;
-                        current_dep = PomDependency {
-                            group_id: String::new(),
-                            artifact_id: String::new(),
-                            version: String::new(),
-                            scope: None,
-                            optional: false,
-                        };
-                    }
-                    "properties" => in_properties = true,
-                    "parent" => in_parent = true,
-                    _ => current_tag = tag,
-                }
+impl PomParser {
+    fn start(&mut self, path: &[&str]) {
+        match path {
+            ["parent"] => self.model.parent = Some(ParentPom::default()),
+            ["distributionManagement", "relocation"] => {
+                self.model.relocation = Some(Relocation::default())
             }
-
-            Ok(Event::End(ref e)) => {
-                let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
-                match tag.as_str() {
-                    "dependency" => {
-                        if !current_dep.group_id.is_empty()
-                            && !current_dep.artifact_id.is_empty()
-                            && !current_dep.version.is_empty()
-                        {
-                            dependencies.push(current_dep.clone());
-                        }
-                        in_dependency = false;
+            ["profiles", "profile"] => self.model.profiles.push(Profile::default()),
+            ["profiles", "profile", "activation", condition] => {
+                if let Some(profile) = self.model.profiles.last_mut() {
+                    let activation = &mut profile.activation;
+                    match *condition {
+                        "os" => activation.os = Some(OsCondition::default()),
+                        "property" => activation.property = Some((String::new(), None)),
+                        "file" => activation.file = true,
+                        _ => {}
                     }
-                    "properties" => in_properties = false,
-                    "parent" => in_parent = false,
-                    _ => {}
                 }
-                current_tag.clear();
             }
+            _ => match dependency_path(content_path(path).1) {
+                Some((_, [])) => self.dependency = PomDependency::default(),
+                Some((_, ["exclusions", "exclusion"])) => self.exclusion = Exclusion::default(),
+                _ => {}
+            },
+        }
+    }
 
-            Ok(Event::Text(e)) => {
-                let value = e.unescape().unwrap_or_default().to_string();
-                if in_properties {
-                    properties.insert(current_tag.clone(), value);
-                } else if in_parent {
-                    match current_tag.as_str() {
+    fn text(&mut self, path: &[&str], value: String) {
+        let model = &mut self.model;
+        match path {
+            ["groupId"] => model.group_id = Some(value),
+            ["artifactId"] => model.artifact_id = value,
+            ["version"] => model.version = Some(value),
+            ["parent", field] => {
+                if let Some(parent) = &mut model.parent {
+                    match *field {
                         "groupId" => parent.group_id = value,
                         "artifactId" => parent.artifact_id = value,
                         "version" => parent.version = value,
                         _ => {}
                     }
-                } else if in_dependency {
-                    match current_tag.as_str() {
-                        "groupId" => {
-                            current_dep.group_id = resolve_placeholders(&value, &properties)
-                        }
-                        "artifactId" => current_dep.artifact_id = value,
-                        "version" => {
-                            current_dep.version = resolve_placeholders(&value, &properties)
+                }
+            }
+            ["distributionManagement", "relocation", field] => {
+                if let Some(relocation) = &mut model.relocation {
+                    match *field {
+                        "groupId" => relocation.group_id = Some(value),
+                        "artifactId" => relocation.artifact_id = Some(value),
+                        "version" => relocation.version = Some(value),
+                        "message" => relocation.message = Some(value),
+                        _ => {}
+                    }
+                }
+            }
+            ["profiles", "profile", "id"] => {
+                if let Some(profile) = model.profiles.last_mut() {
+                    profile.id = value;
+                }
+            }
+            ["profiles", "profile", "activation", condition @ ..] => {
+                if let Some(profile) = model.profiles.last_mut() {
+                    let activation = &mut profile.activation;
+                    match (condition, &mut activation.os, &mut activation.property) {
+                        (["activeByDefault"], ..) => {
+                            activation.active_by_default = value.eq_ignore_ascii_case("true")
                         }
-                        "scope" => current_dep.scope = Some(value),
-                        "optional" => current_dep.optional = value.to_lowercase() == "true",
+                        (["jdk"], ..) => activation.jdk = Some(value),
+                        (["os", "name"], Some(os), _) => os.name = Some(value),
+                        (["os", "family"], Some(os), _) => os.family = Some(value),
+                        (["os", "arch"], Some(os), _) => os.arch = Some(value),
+                        (["os", "version"], Some(os), _) => os.version = Some(value),
+                        (["property", "name"], _, Some(property)) => property.0 = value,
+                        (["property", "value"], _, Some(property)) => property.1 = Some(value),
                         _ => {}
                     }
-                } else {
-                    match current_tag.as_str() {
-                        "artifactId" => artifact_id = value,
-                        "groupId" => group_id = Some(value),
-                        "version" => version = Some(value),
+                }
+            }
+            _ => {
+                let (in_profile, path) = content_path(path);
+                match (path, dependency_path(path)) {
+                    (["properties", name], _) => {
+                        let properties = match model.profiles.last_mut() {
+                            Some(profile) if in_profile => &mut profile.properties,
+                            _ => &mut model.properties,
+                        };
+                        properties.insert(name.to_string(), value);
+                    }
+                    (["repositories", "repository", field], _) => match *field {
+                        "id" => self.repository.id = value,
+                        "url" => self.repository.url = value,
                         _ => {}
+                    },
+                    (_, Some((_, [field]))) => {
+                        let dep = &mut self.dependency;
+                        match *field {
+                            "groupId" => dep.group_id = value,
+                            "artifactId" => dep.artifact_id = value,
+                            "version" => dep.version = value,
+                            "scope" => dep.scope = Some(value),
+                            "type" => dep.dep_type = Some(value),
+                            "classifier" => dep.classifier = Some(value),
+                            "optional" => dep.optional = value.eq_ignore_ascii_case("true"),
+                            _ => {}
+                        }
                     }
+                    (_, Some((_, ["exclusions", "exclusion", field]))) => match *field {
+                        "groupId" => self.exclusion.group_id = value,
+                        "artifactId" => self.exclusion.artifact_id = value,
+                        _ => {}
+                    },
+                    _ => {}
                 }
             }
+        }
+    }
 
-            Ok(Event::Eof) => break,
-            Err(e) => {
-                eprintln!("Error reading POM: {:?}", e);
-                break;
+    fn end(&mut self, path: &[&str]) {
+        let (in_profile, path) = content_path(path);
+        if path == ["repositories", "repository"] {
+            let repository = std::mem::take(&mut self.repository);
+            if !repository.url.is_empty() {
+                let repositories = match self.model.profiles.last_mut() {
+                    Some(profile) if in_profile => &mut profile.repositories,
+                    _ => &mut self.model.repositories,
+                };
+                add_repository(repositories, repository);
+            }
+            return;
+        }
+        match dependency_path(path) {
+            Some((managed, [])) => {
+                let dep = std::mem::take(&mut self.dependency);
+                if dep.group_id.is_empty() || dep.artifact_id.is_empty() {
+                    return;
+                }
+                // Profile content stays in its profile until activation
+                let (dependencies, dependency_management) = match self.model.profiles.last_mut() {
+                    Some(profile) if in_profile => (
+                        &mut profile.dependencies,
+                        &mut profile.dependency_management,
+                    ),
+                    _ => (
+                        &mut self.model.dependencies,
+                        &mut self.model.dependency_management,
+                    ),
+                };
+                if managed {
+                    if !dep.version.is_empty() {
+                        dependency_management.push(dep);
+                    }
+                } else {
+                    // Versionless dependencies are kept so management can fill them in
+                    dependencies.push(dep);
+                }
+            }
+            Some((_, ["exclusions", "exclusion"])) => {
+                let mut exclusion = std::mem::take(&mut self.exclusion);
+                if !exclusion.group_id.is_empty() {
+                    if exclusion.artifact_id.is_empty() {
+                        exclusion.artifact_id = "*".to_string();
+                    }
+                    self.dependency.exclusions.push(exclusion);
+                }
             }
             _ => {}
         }
-
-        buf.clear();
     }
+}
+
+/// Open element names below `<project>`, the paths [`PomParser`] works with.
+fn below_root(elements: &[String]) -> Vec<&str> {
+    elements.iter().skip(1).map(String::as_str).collect()
+}
+
+pub fn parse_pom_model(path: &Path) -> Result<PomModel, PomError> {
+    let xml = fs::read_to_string(path).map_err(|source| PomError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mut reader = Reader::from_str(&xml);
+    reader.trim_text(true);
+
+    let mut parser = PomParser::default();
+    // Open elements from the root down; `<project>` itself is left out of parser paths
+    let mut elements: Vec<String> = Vec::new();
+    let xml_error = |elements: &[String], source| PomError::Xml {
+        path: path.to_path_buf(),
+        element: elements.join("/"),
+        source,
+    };
 
-    // Final fallback jika tidak didefinisikan secara eksplisit
-    let resolved_group_id = group_id.clone().or_else(|| {
-        if !parent.group_id.is_empty() {
-            Some(parent.group_id.clone())
-        } else {
-            None
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|source| xml_error(&elements, source))?;
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                if elements.is_empty() && name != "project" {
+                    return Err(PomError::NotAPom {
+                        path: path.to_path_buf(),
+                    });
+                }
+                elements.push(name);
+                parser.start(&below_root(&elements));
+                if matches!(event, Event::Empty(_)) {
+                    parser.end(&below_root(&elements));
+                    elements.pop();
+                }
+            }
+            Event::End(_) => {
+                parser.end(&below_root(&elements));
+                elements.pop();
+            }
+            Event::Text(e) => {
+                let value = e
+                    .unescape()
+                    .map_err(|source| xml_error(&elements, source))?
+                    .into_owned();
+                parser.text(&below_root(&elements), value);
+            }
+            Event::CData(e) => {
+                let value = String::from_utf8_lossy(&e).into_owned();
+                parser.text(&below_root(&elements), value);
+            }
+            Event::Comment(e)
+                if String::from_utf8_lossy(&e).contains("published-with-gradle-metadata") =>
+            {
+                parser.model.gradle_metadata = true;
+            }
+            Event::Eof => break,
+            _ => {}
         }
-    });
+    }
+    if !elements.is_empty() {
+        return Err(PomError::Truncated {
+            path: path.to_path_buf(),
+            element: elements.join("/"),
+        });
+    }
 
-    let resolved_version = version.clone().or_else(|| {
-        if !parent.version.is_empty() {
-            Some(parent.version.clone())
-        } else {
-            None
+    let mut model = parser.model;
+    if model.artifact_id.is_empty() {
+        return Err(PomError::NotAPom {
+            path: path.to_path_buf(),
+        });
+    }
+    model.parent = model.parent.filter(|parent| !parent.group_id.is_empty());
+    // Coordinates not declared by the POM itself come from its parent
+    if let Some(parent) = &model.parent {
+        if model.group_id.is_none() {
+            model.group_id = Some(parent.group_id.clone());
         }
-    });
+        if model.version.is_none() && !parent.version.is_empty() {
+            model.version = Some(parent.version.clone());
+        }
+    }
+    Ok(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/poms")
+            .join(name)
+    }
+
+    fn parse(name: &str) -> PomModel {
+        parse_pom_model(&fixture(name)).unwrap()
+    }
+
+    fn keys(deps: &[PomDependency]) -> Vec<String> {
+        deps.iter().map(PomDependency::key).collect()
+    }
 
-    PomModel {
-        group_id: resolved_group_id,
-        artifact_id,
-        version: resolved_version,
-        properties,
-        dependencies,
-        parent: if parent.group_id.is_empty() {
-            None
-        } else {
-            Some(parent)
-        },
+    #[test]
+    fn plugin_coordinates_do_not_leak_into_the_project() {
+        let model = parse("plugins.pom");
+        assert_eq!(model.group_id.as_deref(), Some("org.apache.commons"));
+        assert_eq!(model.artifact_id, "commons-text");
+        assert_eq!(model.version.as_deref(), Some("1.10.0"));
+
+        let parent = model.parent.as_ref().unwrap();
+        assert_eq!(parent.artifact_id, "commons-parent");
+        assert_eq!(parent.version, "58");
+
+        assert_eq!(
+            keys(&model.dependencies),
+            [
+                "org.apache.commons:commons-lang3",
+                "org.junit.jupiter:junit-jupiter"
+            ]
+        );
+        assert!(model.dependency_management.is_empty());
+        assert_eq!(model.dependencies[0].version, "${commons.lang3.version}");
+        assert!(model.dependencies[1].version.is_empty());
+        assert_eq!(
+            model
+                .properties
+                .get("commons.lang3.version")
+                .map(String::as_str),
+            Some("3.12.0")
+        );
+    }
+
+    #[test]
+    fn profile_content_stays_in_its_profile() {
+        let model = parse("profiles.pom");
+        assert_eq!(keys(&model.dependencies), ["org.slf4j:slf4j-api"]);
+        assert!(model.dependency_management.is_empty());
+        assert_eq!(
+            model.properties.get("jaxb.version").map(String::as_str),
+            Some("2.3.1")
+        );
+
+        let ids: Vec<_> = model.profiles.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["java9+", "linux", "no-metrics", "release", "legacy"]);
+
+        let [java9, linux, no_metrics, release, legacy] = &model.profiles[..] else {
+            unreachable!();
+        };
+        assert_eq!(java9.activation.jdk.as_deref(), Some("[9,)"));
+        assert_eq!(keys(&java9.dependencies), ["javax.xml.bind:jaxb-api"]);
+        let os = linux.activation.os.as_ref().unwrap();
+        assert_eq!(os.family.as_deref(), Some("unix"));
+        assert_eq!(os.name.as_deref(), Some("!mac os x"));
+        assert_eq!(os.arch.as_deref(), Some("amd64"));
+        assert_eq!(
+            linux.dependencies[0].classifier.as_deref(),
+            Some("linux-x86_64")
+        );
+        assert_eq!(
+            no_metrics.activation.property,
+            Some(("!skipMetrics".to_string(), None))
+        );
+        assert_eq!(
+            keys(&no_metrics.dependency_management),
+            ["io.dropwizard.metrics:metrics-core"]
+        );
+        assert_eq!(
+            release.activation.property,
+            Some(("performRelease".to_string(), Some("true".to_string())))
+        );
+        assert!(legacy.activation.active_by_default);
+    }
+
+    #[test]
+    fn profiles_activate_by_jdk_and_property() {
+        let mut modern = parse("profiles.pom");
+        modern.activate_profiles(&ActivationContext::new(Some(17)));
+        modern.interpolate();
+        let jaxb = modern
+            .dependencies
+            .iter()
+            .find(|d| d.key() == "javax.xml.bind:jaxb-api")
+            .unwrap();
+        assert_eq!(jaxb.version, "2.3.3");
+        // Another profile is active, so the activeByDefault one is not
+        assert!(!keys(&modern.dependencies).contains(&"javax.annotation:jsr250-api".to_string()));
+        assert_eq!(
+            keys(&modern.dependency_management),
+            ["io.dropwizard.metrics:metrics-core"]
+        );
+
+        let mut legacy = parse("profiles.pom");
+        legacy.activate_profiles(&ActivationContext::new(Some(8)));
+        assert!(!keys(&legacy.dependencies).contains(&"javax.xml.bind:jaxb-api".to_string()));
+        assert_eq!(
+            legacy.properties.get("jaxb.version").map(String::as_str),
+            Some("2.3.1")
+        );
+    }
+
+    #[test]
+    fn exclusions_default_to_every_artifact_of_a_group() {
+        let model = parse("exclusions.pom");
+        let [common, annotations, hdfs] = &model.dependencies[..] else {
+            panic!(
+                "expected three dependencies, got {:?}",
+                keys(&model.dependencies)
+            );
+        };
+        assert_eq!(common.scope.as_deref(), Some("compile"));
+        let exclusions: Vec<_> = common
+            .exclusions
+            .iter()
+            .map(|e| format!("{}:{}", e.group_id, e.artifact_id))
+            .collect();
+        assert_eq!(
+            exclusions,
+            [
+                "commons-logging:commons-logging",
+                "org.eclipse.jetty:*",
+                "javax.servlet:*"
+            ]
+        );
+        assert!(annotations.optional && annotations.exclusions.is_empty());
+        assert_eq!(hdfs.dep_type.as_deref(), Some("test-jar"));
+        assert_eq!(hdfs.classifier.as_deref(), Some("tests"));
+        assert_eq!(hdfs.scope.as_deref(), Some("test"));
+    }
+
+    #[test]
+    fn only_dependency_repositories_are_read() {
+        let model = parse("repositories.pom");
+        assert_eq!(model.group_id.as_deref(), Some("mysql"));
+        assert_eq!(model.artifact_id, "mysql-connector-java");
+        assert_eq!(model.version.as_deref(), Some("8.0.33"));
+        assert!(model.dependencies.is_empty());
+        assert!(model.parent.is_none());
+        // Plugin and deployment repositories are not where dependencies come from
+        assert_eq!(
+            model.repositories,
+            [PomRepository {
+                id: "jboss".to_string(),
+                url: "https://repository.jboss.org/nexus/content/groups/public/".to_string(),
+            }]
+        );
+
+        let relocation = model.relocation.as_ref().unwrap();
+        assert_eq!(
+            relocation.target("mysql:mysql-connector-java", "8.0.33"),
+            (
+                "com.mysql:mysql-connector-j".to_string(),
+                "8.0.33".to_string()
+            )
+        );
+        assert!(relocation.message.as_deref().unwrap().starts_with("MySQL"));
+    }
+
+    #[test]
+    fn broken_poms_are_errors() {
+        match parse_pom_model(&fixture("truncated.pom")) {
+            Err(PomError::Truncated { element, .. }) => {
+                assert_eq!(element, "project/dependencies/dependency")
+            }
+            other => panic!("expected Truncated, got {other:?}"),
+        }
+        match parse_pom_model(&fixture("mismatched.pom")) {
+            Err(PomError::Xml { element, .. }) => {
+                assert_eq!(element, "project/dependencies/dependency/artifactId")
+            }
+            other => panic!("expected Xml, got {other:?}"),
+        }
+        assert!(matches!(
+            parse_pom_model(&fixture("html-error-page.pom")),
+            Err(PomError::NotAPom { .. })
+        ));
+        assert!(matches!(
+            parse_pom_model(&fixture("missing.pom")),
+            Err(PomError::Io { .. })
+        ));
     }
 }
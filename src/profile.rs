@@ -0,0 +1,191 @@
+use crate::pom::{PomDependency, PomRepository};
+use crate::version::{ComparableVersion, VersionRange, is_range};
+use std::collections::HashMap;
+use std::env;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// A POM `<profile>`: content that joins the model only when its activation matches.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub id: String,
+    pub activation: Activation,
+    pub properties: HashMap<String, String>,
+    pub dependencies: Vec<PomDependency>,
+    pub dependency_management: Vec<PomDependency>,
+    pub repositories: Vec<PomRepository>,
+}
+
+/// `<activation>`: every condition present must hold (Maven 3.2.2+).
+#[derive(Debug, Clone, Default)]
+pub struct Activation {
+    pub active_by_default: bool,
+    /// `1.8`, `!1.8` or a range such as `[11,)`
+    pub jdk: Option<String>,
+    pub os: Option<OsCondition>,
+    /// `<property>` name (`!name` when it must be absent) and optional value (`!value`)
+    pub property: Option<(String, Option<String>)>,
+    /// A `<file>` condition; files of a dependency's project cannot be checked
+    pub file: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OsCondition {
+    pub name: Option<String>,
+    pub family: Option<String>,
+    pub arch: Option<String>,
+    pub version: Option<String>,
+}
+
+/// What activation is evaluated against: the target JDK and the host OS, described with
+/// the values of Java's `java.version`, `os.name` and `os.arch` system properties.
+pub struct ActivationContext {
+    jdk: Option<String>,
+    os_name: &'static str,
+    os_arch: &'static str,
+}
+
+impl ActivationContext {
+    /// The project's `java_version` when configured, otherwise the JDK on the `PATH`,
+    /// which is what Maven itself would run on.
+    pub fn new(java_version: Option<u32>) -> Self {
+        let jdk = match java_version {
+            Some(8) => Some("1.8".to_string()),
+            Some(version) => Some(version.to_string()),
+            None => host_java_version().clone(),
+        };
+        ActivationContext {
+            jdk,
+            os_name: match env::consts::OS {
+                "linux" => "linux",
+                "macos" => "mac os x",
+                "windows" => "windows",
+                "freebsd" => "freebsd",
+                other => other,
+            },
+            os_arch: match env::consts::ARCH {
+                "x86_64" => "amd64",
+                "x86" => "x86",
+                other => other,
+            },
+        }
+    }
+
+    fn property(&self, name: &str) -> Option<String> {
+        match name {
+            "java.version" => self.jdk.clone(),
+            "os.name" => Some(self.os_name.to_string()),
+            "os.arch" => Some(self.os_arch.to_string()),
+            _ => env::var(name.strip_prefix("env.")?).ok(),
+        }
+    }
+
+    fn jdk_matches(&self, condition: &str) -> bool {
+        let (negated, condition) = negation(condition);
+        let Some(jdk) = &self.jdk else {
+            return false;
+        };
+        let matches = if is_range(condition) {
+            VersionRange::parse(condition).is_some_and(|range| range.contains(jdk))
+        } else {
+            jdk.starts_with(condition)
+                || ComparableVersion::new(jdk) == ComparableVersion::new(condition)
+        };
+        matches != negated
+    }
+
+    fn os_matches(&self, os: &OsCondition) -> bool {
+        let check = |condition: &Option<String>, actual: &dyn Fn(&str) -> bool| {
+            condition.as_deref().is_none_or(|c| {
+                let (negated, c) = negation(c);
+                actual(&c.to_lowercase()) != negated
+            })
+        };
+        check(&os.name, &|name| name == self.os_name)
+            && check(&os.family, &|family| self.is_family(family))
+            && check(&os.arch, &|arch| arch == self.os_arch)
+            // The host OS version is not known, so a version condition never matches
+            && os.version.is_none()
+    }
+
+    /// Maven's OS families: `unix` covers Linux and macOS, `mac` is macOS only.
+    fn is_family(&self, family: &str) -> bool {
+        match family {
+            "windows" | "dos" => self.os_name == "windows",
+            "mac" => self.os_name == "mac os x",
+            "unix" => self.os_name != "windows",
+            other => self.os_name == other,
+        }
+    }
+
+    fn property_matches(&self, name: &str, value: Option<&str>) -> bool {
+        let (negated, name) = negation(name);
+        let actual = self.property(name);
+        if negated {
+            return actual.is_none();
+        }
+        match (actual, value) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(actual), Some(expected)) => {
+                let (negated, expected) = negation(expected);
+                (actual == expected) != negated
+            }
+        }
+    }
+}
+
+impl Activation {
+    /// Whether the conditions of this activation hold. `activeByDefault` is not a
+    /// condition; it is applied per POM in [`active_profiles`].
+    fn matches(&self, context: &ActivationContext) -> bool {
+        let has_condition = self.jdk.is_some() || self.os.is_some() || self.property.is_some();
+        has_condition
+            && !self.file
+            && self
+                .jdk
+                .as_deref()
+                .is_none_or(|jdk| context.jdk_matches(jdk))
+            && self.os.as_ref().is_none_or(|os| context.os_matches(os))
+            && self
+                .property
+                .as_ref()
+                .is_none_or(|(name, value)| context.property_matches(name, value.as_deref()))
+    }
+}
+
+/// The profiles of one POM that are active: those whose conditions hold, or when none
+/// do, the ones marked `activeByDefault`.
+pub fn active_profiles(profiles: Vec<Profile>, context: &ActivationContext) -> Vec<Profile> {
+    let (active, inactive): (Vec<_>, Vec<_>) = profiles
+        .into_iter()
+        .partition(|p| p.activation.matches(context));
+    if !active.is_empty() {
+        return active;
+    }
+    inactive
+        .into_iter()
+        .filter(|p| p.activation.active_by_default)
+        .collect()
+}
+
+/// Split a leading `!` off a condition.
+fn negation(condition: &str) -> (bool, &str) {
+    match condition.strip_prefix('!') {
+        Some(rest) => (true, rest.trim()),
+        None => (false, condition.trim()),
+    }
+}
+
+/// `java.version` of the `java` on the `PATH`, e.g. `17.0.2` or `1.8.0_392`; detected once.
+fn host_java_version() -> &'static Option<String> {
+    static VERSION: OnceLock<Option<String>> = OnceLock::new();
+    VERSION.get_or_init(|| {
+        let output = Command::new("java").arg("-version").output().ok()?;
+        // `java -version` prints e.g. `openjdk version "17.0.2" 2022-01-18` to stderr
+        let text = String::from_utf8_lossy(&output.stderr);
+        let start = text.find('"')? + 1;
+        let end = start + text[start..].find('"')?;
+        Some(text[start..end].to_string())
+    })
+}
@@ -0,0 +1,125 @@
+use crate::config::Config;
+use crate::credentials::{Auth, Credentials};
+use glob::Pattern;
+use std::path::PathBuf;
+
+pub const MAVEN_CENTRAL: &str = "https://repo1.maven.org/maven2";
+
+/// A Maven repository artifacts are looked up in, in declaration order.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    pub name: String,
+    pub url: String,
+    /// Group patterns this repository serves; empty means every group
+    groups: Vec<Pattern>,
+    /// Credentials from the environment or `credentials.toml`, never the project file
+    pub auth: Option<Auth>,
+}
+
+impl Repository {
+    pub fn new(name: &str, url: &str, groups: &[String]) -> Self {
+        let groups = groups
+            .iter()
+            .filter_map(|g| match Pattern::new(g) {
+                Ok(p) => Some(p),
+                Err(e) => {
+                    eprintln!(
+                        "⚠️  Ignoring invalid group pattern '{g}' for repository {name}: {e}"
+                    );
+                    None
+                }
+            })
+            .collect();
+        Repository {
+            name: name.to_string(),
+            url: url.trim_end_matches('/').to_string(),
+            groups,
+            auth: None,
+        }
+    }
+
+    pub fn central() -> Self {
+        Repository::new("central", MAVEN_CENTRAL, &[])
+    }
+
+    /// A repository declared in a dependency's POM. It is named after its URL, since ids
+    /// are only unique within one POM, and never gets credentials.
+    pub fn declared(url: &str) -> Self {
+        let name: String = url
+            .split_once("://")
+            .map_or(url, |(_, rest)| rest)
+            .trim_end_matches('/')
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Repository::new(&name, url, &[])
+    }
+
+    /// Whether this repository's content filter admits `group_id`
+    pub fn serves(&self, group_id: &str) -> bool {
+        self.groups.is_empty() || self.groups.iter().any(|p| p.matches(group_id))
+    }
+
+    /// Attach this repository's credentials to a request, if it has any
+    pub fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.auth {
+            Some(Auth::Basic { username, password }) => {
+                request.basic_auth(username, Some(password))
+            }
+            Some(Auth::Bearer(token)) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Full URL of a repository-relative path such as `org/foo/bar/1.0/bar-1.0.jar`
+    pub fn url_for(&self, rel_path: &str) -> String {
+        format!("{}/{}", self.url, rel_path)
+    }
+}
+
+/// Lower-cased host of an `https://` URL, or `None` for any other scheme.
+pub fn https_host(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    (url.scheme() == "https")
+        .then(|| url.host_str().map(str::to_lowercase))
+        .flatten()
+}
+
+/// Local filesystem path behind a `file://` URL, or `None` for remote URLs.
+pub fn local_path(url: &str) -> Option<PathBuf> {
+    let path = url.strip_prefix("file://")?;
+    // file:///C:/repo on Windows
+    let path = match path.as_bytes() {
+        [b'/', _, b':', ..] => &path[1..],
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+/// The local Maven repository, `~/.m2/repository`.
+pub fn maven_local_repository() -> Option<PathBuf> {
+    std::env::home_dir().map(|home| home.join(".m2").join("repository"))
+}
+
+/// Repositories from `[[repositories]]`, or Maven Central when none are configured,
+/// with their credentials attached.
+pub fn configured_repositories(config: &Config) -> Vec<Repository> {
+    let mut repositories = match &config.repositories {
+        Some(repos) if !repos.is_empty() => repos
+            .iter()
+            .map(|r| Repository::new(&r.name, &r.url, &r.groups))
+            .collect(),
+        _ => vec![Repository::central()],
+    };
+    let mut credentials = Credentials::load();
+    for repo in &mut repositories {
+        repo.auth = credentials.for_repository(&repo.name);
+    }
+    repositories
+}
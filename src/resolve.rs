@@ -0,0 +1,1257 @@
+use crate::config::{Config, DependencySpec, Scope};
+use crate::fetch::{Fetcher, dep_to_path};
+use crate::metadata::{is_dynamic, is_snapshot};
+use crate::module::{ModuleResolution, parse_module};
+use crate::pom::{Exclusion, PomDependency, PomModel, parse_pom_model};
+use crate::profile::ActivationContext;
+use crate::repository::Repository;
+use crate::version::{VersionRange, is_range};
+use futures::future::{BoxFuture, join_all};
+use futures::stream::{FuturesUnordered, StreamExt};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// Resolved classpath written by `fetch` and read by build/run/test/package.
+pub const RESOLVED_FILE: &str = ".rrrgradle/resolved.json";
+
+/// An artifact selected by the resolver, with the single version that won mediation.
+#[derive(Debug, Clone)]
+pub struct ResolvedArtifact {
+    pub dep: String,
+    pub version: String,
+    pub classifier: Option<String>,
+    pub extension: String,
+    pub scope: Scope,
+    pub depth: usize,
+    /// Chain of `group:artifact:version` that introduced this artifact (empty for direct deps)
+    pub path: Vec<String>,
+    /// Declared in `rrrgradle.toml`; its version and scope are never overridden
+    pub direct: bool,
+    /// Repositories declared in the POMs above it that may also serve its files
+    pub repositories: Vec<Repository>,
+    /// Widest scope this artifact's dependencies have been walked with
+    expanded_scope: Option<Scope>,
+}
+
+impl ResolvedArtifact {
+    pub fn group_id(&self) -> &str {
+        self.dep.split(':').next().unwrap_or_default()
+    }
+
+    /// Mediation key, matching [`DependencyEdge::key`]
+    pub fn key(&self) -> String {
+        artifact_key(&self.dep, self.classifier.as_deref(), &self.extension)
+    }
+
+    /// `group:artifact:version[:classifier]`
+    pub fn coordinate(&self) -> String {
+        match &self.classifier {
+            Some(classifier) => format!("{}:{}:{}", self.dep, self.version, classifier),
+            None => format!("{}:{}", self.dep, self.version),
+        }
+    }
+}
+
+/// A requested version that lost against the selected one.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub dep: String,
+    pub requested: String,
+    pub selected: String,
+    pub path: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum ResolveError {
+    #[error(
+        "test declarations overridden by the main classpath:\n{}\n\
+         declare these versions in [dependencies] instead, or drop them from [test_dependencies]",
+        .0.join("\n")
+    )]
+    TestDeclarationOverridden(Vec<String>),
+}
+
+/// What mediation made of one dependency declaration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeOutcome {
+    /// This declaration put the artifact in the graph; its dependencies hang off it
+    Selected,
+    /// The artifact was already in the graph at this version
+    Duplicate,
+    /// Another version of the artifact had already been selected
+    Conflict,
+    /// Pruned by an exclusion declared further up the path
+    Excluded,
+}
+
+/// One dependency declaration in the graph: who declared what, and how it was mediated.
+#[derive(Debug, Clone)]
+pub struct DependencyEdge {
+    /// Mediation key of the declaring artifact; `None` for `rrrgradle.toml`
+    pub parent: Option<String>,
+    /// Mediation key of the declared artifact
+    pub key: String,
+    pub dep: String,
+    pub classifier: Option<String>,
+    /// Version, range or keyword as declared
+    pub requested: String,
+    /// Version in the graph; `None` when excluded
+    pub selected: Option<String>,
+    pub scope: Scope,
+    pub outcome: EdgeOutcome,
+    /// The exclusion pattern that pruned it
+    pub excluded_by: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Resolution {
+    /// Selected artifacts, nearest first
+    pub artifacts: Vec<ResolvedArtifact>,
+    pub conflicts: Vec<Conflict>,
+    /// Every declaration walked, in breadth-first order
+    pub edges: Vec<DependencyEdge>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClasspathEntry {
+    pub path: String,
+    pub scope: Scope,
+    /// The artifact's `-sources` JAR, when it is in the cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sources: Option<String>,
+    /// The artifact's `-javadoc` JAR, when it is in the cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub javadoc: Option<String>,
+}
+
+/// Resolved dependency JARs with their scopes, in classpath order.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ResolvedClasspath {
+    pub entries: Vec<ClasspathEntry>,
+}
+
+/// The classpaths the build tool assembles from resolved dependencies.
+#[derive(Debug, Clone, Copy)]
+pub enum Classpath {
+    /// `javac` for main sources: compile + provided
+    Compile,
+    /// `run` and packaged JARs: compile + runtime
+    Runtime,
+    /// Compiling and running tests: every scope
+    Test,
+}
+
+impl Classpath {
+    pub fn includes(self, scope: Scope) -> bool {
+        match self {
+            Classpath::Compile => matches!(scope, Scope::Compile | Scope::Provided),
+            Classpath::Runtime => matches!(scope, Scope::Compile | Scope::Runtime),
+            Classpath::Test => true,
+        }
+    }
+}
+
+/// A dependency edge waiting to be mediated in the breadth-first walk.
+#[derive(Clone)]
+struct Pending {
+    dep: String,
+    version: String,
+    classifier: Option<String>,
+    extension: String,
+    scope: Scope,
+    path: Vec<String>,
+    /// Exclusions declared along the path, applied to this node's whole subtree
+    exclusions: Vec<Exclusion>,
+    /// Version as declared, before ranges and keywords were made concrete
+    requested: String,
+    /// Mediation key of the declaring artifact; `None` for direct dependencies
+    parent: Option<String>,
+    /// Repositories the POMs along the path declared, allowed by `[fetch] pom_repositories`
+    repositories: Vec<Repository>,
+}
+
+impl Pending {
+    /// Mediation key: artifacts with different classifiers or extensions coexist
+    fn key(&self) -> String {
+        artifact_key(&self.dep, self.classifier.as_deref(), &self.extension)
+    }
+
+    fn edge(&self, selected: &str, outcome: EdgeOutcome) -> DependencyEdge {
+        DependencyEdge {
+            parent: self.parent.clone(),
+            key: self.key(),
+            dep: self.dep.clone(),
+            classifier: self.classifier.clone(),
+            requested: self.requested.clone(),
+            selected: Some(selected.to_string()),
+            scope: self.scope,
+            outcome,
+            excluded_by: None,
+        }
+    }
+}
+
+fn artifact_key(dep: &str, classifier: Option<&str>, extension: &str) -> String {
+    match classifier {
+        None if extension == "jar" => dep.to_string(),
+        _ => format!("{dep}:{extension}:{}", classifier.unwrap_or("")),
+    }
+}
+
+/// Map a Maven `<type>` to the file extension and any classifier it implies.
+fn type_to_extension(dep_type: Option<&str>) -> (String, Option<String>) {
+    match dep_type.unwrap_or("jar") {
+        "test-jar" => ("jar".to_string(), Some("tests".to_string())),
+        "bundle" | "maven-plugin" | "ejb" => ("jar".to_string(), None),
+        other => (other.to_string(), None),
+    }
+}
+
+/// Direct dependencies in declaration order, which is what breaks ties between them.
+fn declared_roots<V: Clone>(deps: Option<&IndexMap<String, V>>) -> Vec<(String, V)> {
+    deps.map(|d| d.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default()
+}
+
+/// Concrete version for a requested one: a range becomes the highest version the artifact's
+/// `maven-metadata.xml` lists inside it, `LATEST`/`RELEASE` the version the metadata names,
+/// and a SNAPSHOT the timestamped build of its newest deploy. Anything else, including
+/// SNAPSHOTs only installed locally, is returned as is.
+async fn concrete_version(
+    dep: &str,
+    version: &str,
+    repositories: &[Repository],
+    fetcher: &Fetcher,
+) -> String {
+    if !is_range(version) && !is_dynamic(version) && !is_snapshot(version) {
+        return version.to_string();
+    }
+    let Some((group_id, artifact_id)) = dep.split_once(':') else {
+        return version.to_string();
+    };
+    let artifact_dir = format!("{}/{}", group_id.replace('.', "/"), artifact_id);
+
+    let mut concrete = version.to_string();
+    if is_range(version) {
+        let Some(range) = VersionRange::parse(version) else {
+            eprintln!("⚠️  Invalid version range {version} for {dep}");
+            return concrete;
+        };
+        match fetcher
+            .fetch_metadata(group_id, &artifact_dir, repositories)
+            .await
+            .and_then(|m| range.select(&m.versions).cloned())
+        {
+            Some(v) => concrete = v,
+            None => {
+                eprintln!("⚠️  No version of {dep} in {range} is listed in maven-metadata.xml");
+                return concrete;
+            }
+        }
+    } else if is_dynamic(version) {
+        match fetcher
+            .fetch_metadata(group_id, &artifact_dir, repositories)
+            .await
+            .and_then(|m| m.dynamic_version(version))
+        {
+            Some(v) => concrete = v,
+            None => {
+                eprintln!("⚠️  No maven-metadata.xml lists a {version} version of {dep}");
+                return concrete;
+            }
+        }
+    }
+    if is_snapshot(&concrete)
+        && let Some(timestamped) = fetcher
+            .fetch_metadata(
+                group_id,
+                &format!("{artifact_dir}/{concrete}"),
+                repositories,
+            )
+            .await
+            .and_then(|m| m.snapshot_version(&concrete))
+    {
+        concrete = timestamped;
+    }
+    concrete
+}
+
+/// Replace ranges, dynamic and SNAPSHOT versions in `nodes` with concrete ones, so
+/// mediation compares, and the lockfile records, the versions actually downloaded.
+async fn concretize(nodes: Vec<Pending>, fetcher: &Fetcher) -> Vec<Pending> {
+    join_all(nodes.into_iter().map(|mut node| async move {
+        let concrete =
+            concrete_version(&node.dep, &node.version, &node.repositories, fetcher).await;
+        if concrete != node.version {
+            eprintln!("→ Resolved {}:{} to {concrete}", node.dep, node.version);
+            node.version = concrete;
+        }
+        node
+    }))
+    .await
+}
+
+/// Download the POM for `dep:version` into the cache and return its path if available.
+/// Parent and BOM versions may still be SNAPSHOTs here, so they are made concrete first.
+async fn fetch_pom(
+    dep: &str,
+    version: &str,
+    is_test: bool,
+    repositories: &[Repository],
+    fetcher: &Fetcher,
+) -> Option<PathBuf> {
+    let version = concrete_version(dep, version, repositories, fetcher).await;
+    let (base_path, _, pom_name) = dep_to_path(dep, &version, None, "jar")?;
+    let group_id = dep.split(':').next().unwrap_or_default();
+    let pom_path = fetcher.cache_dir.join(&base_path).join(&pom_name);
+    fetcher
+        .fetch_file(
+            group_id,
+            &format!("{base_path}/{pom_name}"),
+            &pom_path,
+            is_test,
+            repositories,
+        )
+        .await
+        .then_some(pom_path)
+}
+
+/// Models built during one run, by `group:artifact:version`, so one that many nodes refer
+/// to is fetched and merged once. A coordinate requested again while it is still being
+/// built is built twice; both give the same result.
+#[derive(Default)]
+pub struct ModelCache {
+    /// Effective models, `None` for a POM that could not be fetched or read
+    effective: Mutex<HashMap<String, Option<PomModel>>>,
+    /// What each BOM manages, its own imports expanded
+    boms: Mutex<HashMap<String, Vec<PomDependency>>>,
+}
+
+/// Expand `<dependencyManagement>` entries into a managed-version map, fetching and merging
+/// `<scope>import</scope>` BOMs. Earlier entries win, so local declarations take precedence
+/// over imported ones, and earlier imports over later ones.
+async fn managed_versions(
+    entries: Vec<PomDependency>,
+    is_test: bool,
+    java_version: Option<u32>,
+    repositories: &[Repository],
+    fetcher: &Fetcher,
+) -> HashMap<String, PomDependency> {
+    let mut managed = HashMap::new();
+    for entry in expand_imports(entries, is_test, java_version, repositories, fetcher, &[]).await {
+        managed.entry(entry.key()).or_insert(entry);
+    }
+    managed
+}
+
+/// `entries` in precedence order: the local ones first, then what each import manages, in
+/// the order they are imported. `importing` is the chain of BOMs being expanded.
+fn expand_imports<'a>(
+    entries: Vec<PomDependency>,
+    is_test: bool,
+    java_version: Option<u32>,
+    repositories: &'a [Repository],
+    fetcher: &'a Fetcher,
+    importing: &'a [String],
+) -> BoxFuture<'a, Vec<PomDependency>> {
+    Box::pin(async move {
+        let (imports, mut expanded): (Vec<_>, Vec<_>) =
+            entries.into_iter().partition(|e| e.is_bom_import());
+        for import in imports {
+            let coordinate = format!("{}:{}", import.key(), import.version);
+            if importing.contains(&coordinate) {
+                eprintln!("⚠️  Cyclic BOM import {coordinate}");
+                continue;
+            }
+            let cached = fetcher
+                .models
+                .boms
+                .lock()
+                .unwrap()
+                .get(&coordinate)
+                .cloned();
+            let managed = match cached {
+                Some(managed) => managed,
+                None => {
+                    let managed = match effective_model(
+                        &import.key(),
+                        &import.version,
+                        is_test,
+                        java_version,
+                        repositories,
+                        fetcher,
+                    )
+                    .await
+                    {
+                        Some(bom) => {
+                            let mut chain = importing.to_vec();
+                            chain.push(coordinate.clone());
+                            expand_imports(
+                                bom.dependency_management,
+                                is_test,
+                                java_version,
+                                repositories,
+                                fetcher,
+                                &chain,
+                            )
+                            .await
+                        }
+                        None => {
+                            eprintln!("⚠️  Could not import BOM {coordinate}");
+                            Vec::new()
+                        }
+                    };
+                    let mut boms = fetcher.models.boms.lock().unwrap();
+                    boms.entry(coordinate).or_insert(managed).clone()
+                }
+            };
+            expanded.extend(managed);
+        }
+        expanded
+    })
+}
+
+/// Treat each `[platforms]` entry as a BOM import of the project itself.
+async fn platform_versions(config: &Config, fetcher: &Fetcher) -> HashMap<String, PomDependency> {
+    let java_version = config.project.java_version;
+    let imports = declared_roots(config.platforms.as_ref())
+        .into_iter()
+        .filter_map(|(dep, version)| {
+            let (group_id, artifact_id) = dep.split_once(':')?;
+            Some(PomDependency {
+                group_id: group_id.to_string(),
+                artifact_id: artifact_id.to_string(),
+                version,
+                scope: Some("import".to_string()),
+                dep_type: Some("pom".to_string()),
+                ..Default::default()
+            })
+        })
+        .collect();
+    managed_versions(imports, false, java_version, &[], fetcher).await
+}
+
+/// `inherited` plus the repositories `dep:version`'s POM declares that may be used for
+/// what it refers to, per `[fetch] pom_repositories`.
+fn subtree_repositories(
+    inherited: &[Repository],
+    model: &PomModel,
+    dep: &str,
+    version: &str,
+    fetcher: &Fetcher,
+) -> Vec<Repository> {
+    let mut repositories = inherited.to_vec();
+    for repository in fetcher.pom_repositories(&model.repositories, &format!("{dep}:{version}")) {
+        if !repositories.iter().any(|r| r.url == repository.url) {
+            repositories.push(repository);
+        }
+    }
+    repositories
+}
+
+/// Parse a fetched POM, reporting a malformed one instead of failing the resolution.
+fn read_pom(path: &Path) -> Option<PomModel> {
+    parse_pom_model(path)
+        .inspect_err(|e| eprintln!("⚠️  {e}"))
+        .ok()
+}
+
+/// The effective model of `dep:version`, built once per run; see [`build_effective_model`].
+async fn effective_model(
+    dep: &str,
+    version: &str,
+    is_test: bool,
+    java_version: Option<u32>,
+    repositories: &[Repository],
+    fetcher: &Fetcher,
+) -> Option<PomModel> {
+    let coordinate = format!("{dep}:{version}");
+    let cached = fetcher
+        .models
+        .effective
+        .lock()
+        .unwrap()
+        .get(&coordinate)
+        .cloned();
+    if let Some(model) = cached {
+        return model;
+    }
+    let model =
+        build_effective_model(dep, version, is_test, java_version, repositories, fetcher).await;
+    let mut effective = fetcher.models.effective.lock().unwrap();
+    effective.entry(coordinate).or_insert(model).clone()
+}
+
+/// Fetch the POM for `dep:version` and its parent chain, and build the effective model:
+/// parents merged from the top down, then placeholders resolved in the child's context.
+/// Each POM's profiles are activated against `java_version` and the host OS before it is
+/// merged, as Maven does, and the repositories it declares are searched for its parent.
+async fn build_effective_model(
+    dep: &str,
+    version: &str,
+    is_test: bool,
+    java_version: Option<u32>,
+    repositories: &[Repository],
+    fetcher: &Fetcher,
+) -> Option<PomModel> {
+    let context = ActivationContext::new(java_version);
+    let read = |path: &Path| {
+        let mut model = read_pom(path)?;
+        model.activate_profiles(&context);
+        Some(model)
+    };
+    let pom_path = fetch_pom(dep, version, is_test, repositories, fetcher).await?;
+    let mut lineage = vec![read(&pom_path)?];
+    let mut repositories = subtree_repositories(repositories, &lineage[0], dep, version, fetcher);
+    let mut seen = HashSet::from([format!("{dep}:{version}")]);
+
+    while let Some(parent) = lineage.last().and_then(|m| m.parent.clone()) {
+        let parent_dep = format!("{}:{}", parent.group_id, parent.artifact_id);
+        if !seen.insert(format!("{parent_dep}:{}", parent.version)) {
+            eprintln!("⚠️  Cyclic parent POM {parent_dep}:{}", parent.version);
+            break;
+        }
+        match fetch_pom(
+            &parent_dep,
+            &parent.version,
+            is_test,
+            &repositories,
+            fetcher,
+        )
+        .await
+        {
+            Some(path) => match read(&path) {
+                Some(model) => {
+                    repositories = subtree_repositories(
+                        &repositories,
+                        &model,
+                        &parent_dep,
+                        &parent.version,
+                        fetcher,
+                    );
+                    lineage.push(model);
+                }
+                None => break,
+            },
+            None => {
+                eprintln!(
+                    "⚠️  Could not fetch parent POM {parent_dep}:{} of {dep}:{version}",
+                    parent.version
+                );
+                break;
+            }
+        }
+    }
+
+    let mut effective = lineage.pop()?;
+    while let Some(mut child) = lineage.pop() {
+        child.inherit_from(&effective);
+        effective = child;
+    }
+    effective.interpolate();
+    Some(effective)
+}
+
+/// Fetch and interpret the Gradle Module Metadata published next to `dep:version`'s POM.
+/// A POM may advertise metadata that was never uploaded, so a miss falls back quietly.
+async fn module_resolution(
+    dep: &str,
+    version: &str,
+    is_test: bool,
+    java_version: Option<u32>,
+    repositories: &[Repository],
+    fetcher: &Fetcher,
+) -> Option<ModuleResolution> {
+    let (base_path, module_name, _) = dep_to_path(dep, version, None, "module")?;
+    let group_id = dep.split(':').next().unwrap_or_default();
+    let module_path = fetcher.cache_dir.join(&base_path).join(&module_name);
+    if !fetcher
+        .fetch_optional_file(
+            group_id,
+            &format!("{base_path}/{module_name}"),
+            &module_path,
+            is_test,
+            repositories,
+        )
+        .await
+    {
+        return None;
+    }
+    parse_module(&module_path)?.resolve(java_version)
+}
+
+/// Build the model for `dep:version`: the effective POM with its dependency management
+/// applied, or, when the POM says Gradle Module Metadata is published, the dependencies of
+/// the variants a JVM consumer would select, versioned by the platforms they import. The
+/// flag is set when the selected variant is published by another module, which then
+/// becomes this module's only dependency.
+async fn load_model(
+    dep: &str,
+    version: &str,
+    is_test: bool,
+    java_version: Option<u32>,
+    repositories: &[Repository],
+    fetcher: &Fetcher,
+) -> Option<(PomModel, bool)> {
+    let mut model =
+        effective_model(dep, version, is_test, java_version, repositories, fetcher).await?;
+    let repositories = subtree_repositories(repositories, &model, dep, version, fetcher);
+    if model.gradle_metadata {
+        match module_resolution(dep, version, is_test, java_version, &repositories, fetcher).await {
+            Some(ModuleResolution::Dependencies {
+                dependencies,
+                platforms,
+            }) => {
+                let managed =
+                    managed_versions(platforms, is_test, java_version, &repositories, fetcher)
+                        .await;
+                for unversioned in dependencies
+                    .iter()
+                    .filter(|d| d.version.is_empty() && !managed.contains_key(&d.key()))
+                {
+                    eprintln!(
+                        "⚠️  Skipping {} of {}:{}: no version in its Gradle Module Metadata",
+                        unversioned.key(),
+                        dep,
+                        version
+                    );
+                }
+                model.dependencies = dependencies;
+                model.apply_dependency_management(&managed);
+                return Some((model, false));
+            }
+            Some(ModuleResolution::AvailableAt(target)) => {
+                model.dependencies = vec![PomDependency {
+                    group_id: target.group,
+                    artifact_id: target.module,
+                    version: target.version,
+                    ..Default::default()
+                }];
+                return Some((model, true));
+            }
+            None => {}
+        }
+    }
+    let management = std::mem::take(&mut model.dependency_management);
+    let managed = managed_versions(management, is_test, java_version, &repositories, fetcher).await;
+    model.apply_dependency_management(&managed);
+    Some((model, false))
+}
+
+/// Result of expanding one node of the graph.
+#[derive(Default)]
+struct Expansion {
+    /// The node's dependencies, with concrete versions
+    next: Vec<Pending>,
+    /// The node's artifact is published by another module or was relocated
+    redirected: bool,
+    excluded: Vec<DependencyEdge>,
+}
+
+/// Fetch the POM of one node and collect its edges, with their versions made concrete.
+/// `slot` is passed back so the caller can match the result to the node.
+///
+/// Versions managed by the project's platforms override whatever the POMs request,
+/// like a root `<dependencyManagement>` does in Maven.
+async fn expand_node(
+    slot: usize,
+    node: Pending,
+    platforms: &HashMap<String, PomDependency>,
+    java_version: Option<u32>,
+    fetcher: &Fetcher,
+) -> (usize, Expansion) {
+    let mut expansion = Expansion::default();
+    let model = load_model(
+        &node.dep,
+        &node.version,
+        node.scope == Scope::Test,
+        java_version,
+        &node.repositories,
+        fetcher,
+    )
+    .await;
+    let Some((mut model, mut available_elsewhere)) = model else {
+        return (slot, expansion);
+    };
+    let parent = node.key();
+    if let Some(relocation) = model.relocation.take() {
+        let (dep, version) = relocation.target(&node.dep, &node.version);
+        let note = relocation
+            .message
+            .map(|m| format!(" ({})", m.trim()))
+            .unwrap_or_default();
+        if node.parent.is_none() {
+            eprintln!(
+                "⚠️  {}:{} has moved to {dep}:{version}{note}; declare the new coordinates in rrrgradle.toml",
+                node.dep, node.version
+            );
+        } else {
+            eprintln!(
+                "→ {}:{} relocated to {dep}:{version}{note}",
+                node.dep, node.version
+            );
+        }
+        // The relocated artifact stands in for this one, same classifier and type
+        let (group_id, artifact_id) = dep.split_once(':').unwrap_or_default();
+        model.dependencies = vec![PomDependency {
+            group_id: group_id.to_string(),
+            artifact_id: artifact_id.to_string(),
+            version,
+            classifier: node.classifier.clone(),
+            dep_type: (node.extension != "jar").then(|| node.extension.clone()),
+            ..Default::default()
+        }];
+        available_elsewhere = true;
+    }
+    expansion.redirected = available_elsewhere;
+    let repositories = subtree_repositories(
+        &node.repositories,
+        &model,
+        &node.dep,
+        &node.version,
+        fetcher,
+    );
+    let mut path = node.path;
+    path.push(format!("{}:{}", node.dep, node.version));
+    for dep in model.dependencies.into_iter().filter(|dep| !dep.optional) {
+        let Some(scope) =
+            Scope::from_pom(dep.scope.as_deref()).and_then(|child| node.scope.transitive(child))
+        else {
+            continue;
+        };
+        let (extension, implied_classifier) = type_to_extension(dep.dep_type.as_deref());
+        let classifier = dep.classifier.clone().or(implied_classifier);
+        if let Some(exclusion) = node
+            .exclusions
+            .iter()
+            .find(|e| e.matches(&dep.group_id, &dep.artifact_id))
+        {
+            expansion.excluded.push(DependencyEdge {
+                parent: Some(parent.clone()),
+                key: artifact_key(&dep.key(), classifier.as_deref(), &extension),
+                dep: dep.key(),
+                classifier,
+                requested: dep.version.clone(),
+                selected: None,
+                scope,
+                outcome: EdgeOutcome::Excluded,
+                excluded_by: Some(format!("{}:{}", exclusion.group_id, exclusion.artifact_id)),
+            });
+            continue;
+        }
+        if format!("{}{}", dep.key(), dep.version).contains("${") {
+            eprintln!(
+                "⚠️  Unresolved placeholder in {}:{} (from {}:{}), skipping",
+                dep.key(),
+                dep.version,
+                node.dep,
+                node.version
+            );
+            continue;
+        }
+        let version = match platforms.get(&dep.key()) {
+            Some(managed) => managed.version.clone(),
+            None => dep.version.clone(),
+        };
+        let mut exclusions = node.exclusions.clone();
+        exclusions.extend(dep.exclusions.iter().cloned());
+        expansion.next.push(Pending {
+            dep: dep.key(),
+            requested: version.clone(),
+            version,
+            classifier,
+            extension,
+            scope,
+            path: path.clone(),
+            exclusions,
+            parent: Some(parent.clone()),
+            repositories: repositories.clone(),
+        });
+    }
+    expansion.next = concretize(expansion.next, fetcher).await;
+    (slot, expansion)
+}
+
+/// Direct dependencies of one configuration, with omitted versions taken from the platforms.
+fn direct_roots(
+    deps: Option<&IndexMap<String, DependencySpec>>,
+    default_scope: Scope,
+    platforms: &HashMap<String, PomDependency>,
+) -> Vec<Pending> {
+    declared_roots(deps)
+        .into_iter()
+        .filter_map(|(dep, spec)| {
+            let version = if !spec.version().is_empty() {
+                spec.version().to_string()
+            } else if let Some(managed) = platforms.get(&dep) {
+                managed.version.clone()
+            } else {
+                eprintln!("⚠️  No version for {dep} and no platform manages it, skipping");
+                return None;
+            };
+            let (extension, implied_classifier) = type_to_extension(spec.dep_type());
+            Some(Pending {
+                dep,
+                requested: version.clone(),
+                version,
+                classifier: spec.classifier().map(str::to_string).or(implied_classifier),
+                extension,
+                scope: spec.scope(default_scope),
+                path: Vec::new(),
+                exclusions: spec.exclude().iter().map(|e| Exclusion::parse(e)).collect(),
+                parent: None,
+                repositories: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+/// Build the full dependency graph and pick one version per artifact.
+///
+/// Uses Maven's nearest-wins rule: the graph is walked breadth-first and the first
+/// version seen for a `group:artifact` (plus classifier and extension) is selected; later
+/// requests for another version are recorded as conflicts and their subtrees are not
+/// followed, unless they asked for a range the selected version satisfies. Ranges, `LATEST`,
+/// `RELEASE` and SNAPSHOTs are made concrete before they are mediated. Direct declarations
+/// in `rrrgradle.toml` are seeded before the walk, so their
+/// version and scope always win; they may leave the version empty when one of the
+/// `[platforms]` BOMs manages it. When a transitive artifact is reached through paths
+/// with different scopes, the widest scope wins.
+///
+/// Test-scoped roots are seeded and walked only after everything else, so adding a test
+/// dependency never changes the compile or runtime classpath: one the main graph already
+/// selected stays there with its main scope. Since the test classpath shares that version,
+/// a test declaration of another version is an error rather than silently overridden.
+pub async fn resolve_graph(config: &Config, fetcher: &Fetcher) -> Result<Resolution, ResolveError> {
+    let platforms = platform_versions(config, fetcher).await;
+    // Test roots are what `[test_dependencies]` declares, plus `test`-scoped entries of
+    // `[dependencies]`; whatever scope the test table names, they stay off main classpaths
+    let (mut test_roots, main_roots): (Vec<_>, Vec<_>) =
+        direct_roots(config.dependencies.as_ref(), Scope::Compile, &platforms)
+            .into_iter()
+            .partition(|root| root.scope == Scope::Test);
+    for mut root in direct_roots(config.test_dependencies.as_ref(), Scope::Test, &platforms) {
+        if root.scope != Scope::Test {
+            eprintln!(
+                "⚠️  {} is in [test_dependencies], so it is test-scoped, not {}",
+                root.dep,
+                root.scope.as_str()
+            );
+            root.scope = Scope::Test;
+        }
+        test_roots.push(root);
+    }
+    let main_roots = concretize(main_roots, fetcher).await;
+    let test_roots = concretize(test_roots, fetcher).await;
+
+    let mut selected: HashMap<String, ResolvedArtifact> = HashMap::new();
+    let mut order: Vec<String> = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut edges = Vec::new();
+
+    for roots in [&main_roots, &test_roots] {
+        // A test root the main graph already selected keeps that scope, and must want its version
+        let mut overridden = Vec::new();
+        for root in roots {
+            match selected.entry(root.key()) {
+                Entry::Occupied(slot)
+                    if slot.get().version != root.version
+                        && !VersionRange::parse(&root.requested)
+                            .is_some_and(|r| r.contains(&slot.get().version)) =>
+                {
+                    let main = slot.get();
+                    overridden.push(format!(
+                        "  {}:{} (main classpath has {} via {})",
+                        root.dep,
+                        root.requested,
+                        main.version,
+                        if main.path.is_empty() {
+                            "[dependencies]".to_string()
+                        } else {
+                            main.path.join(" → ")
+                        }
+                    ));
+                }
+                Entry::Occupied(_) => {}
+                Entry::Vacant(slot) => {
+                    slot.insert(ResolvedArtifact {
+                        dep: root.dep.clone(),
+                        version: root.version.clone(),
+                        classifier: root.classifier.clone(),
+                        extension: root.extension.clone(),
+                        scope: root.scope,
+                        depth: 1,
+                        path: Vec::new(),
+                        direct: true,
+                        repositories: Vec::new(),
+                        expanded_scope: None,
+                    });
+                    order.push(root.key());
+                }
+            }
+        }
+        if !overridden.is_empty() {
+            return Err(ResolveError::TestDeclarationOverridden(overridden));
+        }
+        // Nodes are mediated strictly in breadth-first order, which is what nearest-wins
+        // needs, but each selected node's POM starts loading the moment it is selected;
+        // `expanding` holds their slots in that order, `loads` the loads still in flight.
+        let mut queue: VecDeque<Pending> = roots.iter().cloned().collect();
+        let mut expanding = VecDeque::new();
+        let mut loads = FuturesUnordered::new();
+        let mut loaded = HashMap::new();
+        let mut slots = 0;
+
+        loop {
+            while let Some(mut node) = queue.pop_front() {
+                let expand = match selected.get_mut(&node.key()) {
+                    Some(sel)
+                        if sel.version != node.version
+                            && !VersionRange::parse(&node.requested)
+                                .is_some_and(|r| r.contains(&sel.version)) =>
+                    {
+                        edges.push(node.edge(&sel.version, EdgeOutcome::Conflict));
+                        conflicts.push(Conflict {
+                            dep: node.dep,
+                            requested: node.version,
+                            selected: sel.version.clone(),
+                            path: node.path,
+                        });
+                        None
+                    }
+                    Some(sel) => {
+                        let outcome = if node.parent.is_none() {
+                            EdgeOutcome::Selected
+                        } else {
+                            EdgeOutcome::Duplicate
+                        };
+                        edges.push(node.edge(&sel.version, outcome));
+                        if !sel.direct {
+                            sel.scope = sel.scope.max(node.scope);
+                        }
+                        // Walk again only when the scope widened, so the subtree widens too
+                        if sel.expanded_scope.is_some_and(|s| s >= sel.scope) {
+                            continue;
+                        }
+                        sel.expanded_scope = Some(sel.scope);
+                        node.scope = sel.scope;
+                        // A range request may have picked another version inside the range
+                        node.version = sel.version.clone();
+                        Some(node)
+                    }
+                    None => {
+                        edges.push(node.edge(&node.version, EdgeOutcome::Selected));
+                        selected.insert(
+                            node.key(),
+                            ResolvedArtifact {
+                                dep: node.dep.clone(),
+                                version: node.version.clone(),
+                                classifier: node.classifier.clone(),
+                                extension: node.extension.clone(),
+                                scope: node.scope,
+                                depth: node.path.len() + 1,
+                                path: node.path.clone(),
+                                direct: false,
+                                repositories: node.repositories.clone(),
+                                expanded_scope: Some(node.scope),
+                            },
+                        );
+                        order.push(node.key());
+                        Some(node)
+                    }
+                };
+                if let Some(node) = expand {
+                    expanding.push_back((slots, node.key()));
+                    loads.push(expand_node(
+                        slots,
+                        node,
+                        &platforms,
+                        config.project.java_version,
+                        fetcher,
+                    ));
+                    slots += 1;
+                }
+            }
+            let Some((slot, key)) = expanding.pop_front() else {
+                break;
+            };
+            // Wait for the oldest expansion; ones that finish first are kept for their turn
+            let expansion = loop {
+                if let Some(expansion) = loaded.remove(&slot) {
+                    break expansion;
+                }
+                let (done, expansion) = loads.next().await.expect("an expansion is in flight");
+                loaded.insert(done, expansion);
+            };
+            edges.extend(expansion.excluded);
+            if expansion.redirected {
+                // Only the POM and `.module` live here; the files come with the dependency
+                if let Some(artifact) = selected.get_mut(&key) {
+                    artifact.extension = "pom".to_string();
+                }
+            }
+            queue.extend(expansion.next);
+        }
+    }
+
+    let mut artifacts: Vec<_> = order
+        .into_iter()
+        .filter_map(|key| selected.remove(&key))
+        .collect();
+    artifacts.sort_by_key(|a| (a.scope == Scope::Test, a.depth));
+
+    Ok(Resolution {
+        artifacts,
+        conflicts,
+        edges,
+    })
+}
+
+pub fn write_resolved_classpath(classpath: &ResolvedClasspath) {
+    let json = serde_json::to_string_pretty(classpath).expect("Failed to serialize classpath");
+    if let Some(parent) = Path::new(RESOLVED_FILE).parent() {
+        fs::create_dir_all(parent).expect("Failed to create .rrrgradle directory");
+    }
+    fs::write(RESOLVED_FILE, json).expect("Failed to write resolved classpath");
+}
+
+/// The classpath recorded by the last `fetch`, or `None` before the first one.
+pub fn read_resolved_classpath() -> Option<ResolvedClasspath> {
+    let content = fs::read_to_string(RESOLVED_FILE).ok()?;
+    Some(serde_json::from_str(&content).unwrap_or_default())
+}
+
+/// Dependency JARs on the given classpath, as selected by the last `fetch`.
+pub fn load_classpath(classpath: Classpath) -> Vec<String> {
+    read_resolved_classpath()
+        .unwrap_or_default()
+        .entries
+        .into_iter()
+        .filter(|entry| classpath.includes(entry.scope))
+        .map(|entry| entry.path)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, TempDir, repository_url};
+
+    /// Resolve `[dependencies]` and `[test_dependencies]` (TOML table bodies) against the
+    /// `graph` fixture repository, where `app:1.0` depends on `util:1.0`.
+    async fn resolve(
+        test: &str,
+        dependencies: &str,
+        test_dependencies: &str,
+    ) -> Result<Resolution, ResolveError> {
+        let config = fixtures::config(&format!(
+            r#"
+            [dependencies]
+            {dependencies}
+
+            [test_dependencies]
+            {test_dependencies}
+
+            [fetch]
+            checksums = "ignore"
+
+            [[repositories]]
+            name = "graph"
+            url = "{}"
+            "#,
+            repository_url("graph")
+        ));
+        let cache = TempDir::new(test);
+        resolve_graph(&config, &fixtures::fetcher(&config, &cache)).await
+    }
+
+    fn selected(resolution: &Resolution, dep: &str) -> (String, Scope) {
+        let artifact = resolution
+            .artifacts
+            .iter()
+            .find(|a| a.dep == dep)
+            .unwrap_or_else(|| panic!("{dep} not selected"));
+        (artifact.version.clone(), artifact.scope)
+    }
+
+    #[tokio::test]
+    async fn test_root_on_main_graph_keeps_main_scope() {
+        let resolution = resolve(
+            "resolve-same-version",
+            r#""com.example:app" = "1.0""#,
+            r#""com.example:util" = "1.0"
+               "com.example:testkit" = "1.0""#,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            selected(&resolution, "com.example:util"),
+            ("1.0".to_string(), Scope::Compile)
+        );
+        assert_eq!(
+            selected(&resolution, "com.example:testkit"),
+            ("1.0".to_string(), Scope::Test)
+        );
+        assert!(resolution.conflicts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_table_scope_never_reaches_main_classpaths() {
+        let resolution = resolve(
+            "resolve-test-table-scope",
+            "",
+            r#""com.example:testkit" = { version = "1.0", scope = "runtime" }"#,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            selected(&resolution, "com.example:testkit"),
+            ("1.0".to_string(), Scope::Test)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_root_overridden_by_main_graph_is_an_error() {
+        let error = resolve(
+            "resolve-other-version",
+            r#""com.example:app" = "1.0""#,
+            r#""com.example:util" = "2.0""#,
+        )
+        .await
+        .unwrap_err();
+
+        let ResolveError::TestDeclarationOverridden(overridden) = error;
+        assert_eq!(overridden.len(), 1);
+        assert!(
+            overridden[0].contains("com.example:util:2.0"),
+            "{overridden:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn roots_keep_declaration_order() {
+        let resolution = resolve(
+            "resolve-declaration-order",
+            r#""com.example:util" = "1.0"
+               "com.example:app" = "1.0""#,
+            "",
+        )
+        .await
+        .unwrap();
+
+        let roots: Vec<_> = resolution
+            .artifacts
+            .iter()
+            .filter(|a| a.direct)
+            .map(|a| a.dep.as_str())
+            .collect();
+        assert_eq!(roots, ["com.example:util", "com.example:app"]);
+    }
+
+    /// Load the model of `com.example:{artifact}:1.0` from the `models` fixture repository.
+    async fn model(test: &str, artifact: &str) -> (PomModel, Fetcher, TempDir) {
+        let config = fixtures::config(&format!(
+            r#"
+            [fetch]
+            checksums = "ignore"
+
+            [[repositories]]
+            name = "models"
+            url = "{}"
+            "#,
+            repository_url("models")
+        ));
+        let cache = TempDir::new(test);
+        let fetcher = fixtures::fetcher(&config, &cache);
+        let dep = format!("com.example:{artifact}");
+        let (model, _) = load_model(&dep, "1.0", false, None, &[], &fetcher)
+            .await
+            .unwrap();
+        (model, fetcher, cache)
+    }
+
+    fn versions(model: &PomModel) -> Vec<(String, String)> {
+        model
+            .dependencies
+            .iter()
+            .map(|d| (d.artifact_id.clone(), d.version.clone()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn bom_imports_yield_to_local_management_and_earlier_imports() {
+        let (model, fetcher, _cache) = model("resolve-bom-precedence", "consumer").await;
+
+        // `extra` comes from a BOM the first import imports itself
+        assert_eq!(
+            versions(&model),
+            [
+                ("lib", "1.1"),
+                ("other", "3.0"),
+                ("extra", "1.5"),
+                ("only-b", "2.0")
+            ]
+            .map(|(a, v)| (a.to_string(), v.to_string()))
+        );
+        let boms = fetcher.models.boms.lock().unwrap();
+        assert!(boms.contains_key("com.example:bom-a:1.0"));
+        assert!(boms.contains_key("com.example:bom-nested:1.0"));
+    }
+
+    #[tokio::test]
+    async fn parents_are_inherited_and_interpolated_across_levels() {
+        let (model, fetcher, _cache) = model("resolve-parent-chain", "child").await;
+
+        assert_eq!(model.group_id.as_deref(), Some("com.example"));
+        assert_eq!(model.version.as_deref(), Some("1.0"));
+        // The child's `lib.version` overrides the grandparent's; the parent's `util.version`
+        // does too, for the dependency the parent declares; `managed` is managed two levels up
+        let mut versions = versions(&model);
+        versions.sort();
+        assert_eq!(
+            versions,
+            [
+                ("lib", "1.2"),
+                ("managed", "4.0"),
+                ("sibling", "1.0"),
+                ("util", "2.0")
+            ]
+            .map(|(a, v)| (a.to_string(), v.to_string()))
+        );
+        assert!(
+            model
+                .dependencies
+                .iter()
+                .all(|d| d.group_id == "com.example")
+        );
+        let effective = fetcher.models.effective.lock().unwrap();
+        assert!(effective.contains_key("com.example:child:1.0"));
+    }
+
+    #[test]
+    fn classpaths_include_scopes_like_maven() {
+        use Scope::{Compile, Provided, Runtime, Test};
+        let scopes = [Compile, Provided, Runtime, Test];
+        for (classpath, row) in [
+            (Classpath::Compile, [true, true, false, false]),
+            (Classpath::Runtime, [true, false, true, false]),
+            (Classpath::Test, [true, true, true, true]),
+        ] {
+            for (scope, expected) in scopes.into_iter().zip(row) {
+                assert_eq!(
+                    classpath.includes(scope),
+                    expected,
+                    "{classpath:?} {scope:?}"
+                );
+            }
+        }
+    }
+}
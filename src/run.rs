@@ -1,4 +1,5 @@
 use crate::config::Config;
+use crate::resolve::{Classpath, load_classpath};
 use std::process::Command;
 
 pub fn run_project(config: &Config) {
@@ -9,18 +10,12 @@ pub fn run_project(config: &Config) {
         .and_then(|m| m.output.as_deref())
         .unwrap_or("build/classes/java/main");
 
-    // Build classpath: main classes + all dependency JARs
+    // Build classpath: main classes + main dependency JARs
     let sep = if cfg!(windows) { ";" } else { ":" };
     let mut cp_parts = vec![main_output.to_string()];
 
-    // Add all JARs from cache
-    if let Ok(entries) = std::fs::read_dir(".rrrgradle/cache") {
-        for entry in entries.filter_map(|e| e.ok()) {
-            if entry.path().extension().map_or(false, |ext| ext == "jar") {
-                cp_parts.push(entry.path().to_string_lossy().to_string());
-            }
-        }
-    }
+    // Add dependency JARs selected by the resolver
+    cp_parts.extend(load_classpath(Classpath::Runtime));
 
     let classpath = cp_parts.join(sep);
 
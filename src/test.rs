@@ -1,4 +1,5 @@
 use crate::config::Config;
+use crate::resolve::{Classpath, load_classpath};
 use std::path::Path;
 use std::process::Command;
 use walkdir::WalkDir;
@@ -74,14 +75,8 @@ pub fn test_project(config: &Config) {
     let sep = if cfg!(windows) { ";" } else { ":" };
     let mut cp_parts = vec![test_output.to_string(), main_output.to_string()];
 
-    // Add all JARs from cache
-    if let Ok(entries) = std::fs::read_dir(".rrrgradle/cache") {
-        for entry in entries.filter_map(|e| e.ok()) {
-            if entry.path().extension().map_or(false, |ext| ext == "jar") {
-                cp_parts.push(entry.path().to_string_lossy().to_string());
-            }
-        }
-    }
+    // Add main and test dependency JARs selected by the resolver
+    cp_parts.extend(load_classpath(Classpath::Test));
 
     let classpath = cp_parts.join(sep);
 
@@ -0,0 +1,381 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Qualifiers in release order; unknown qualifiers sort after all of them, alphabetically.
+const QUALIFIERS: [&str; 7] = ["alpha", "beta", "milestone", "rc", "snapshot", "", "sp"];
+
+/// Index of the empty qualifier, i.e. a plain release
+const RELEASE_INDEX: usize = 5;
+
+#[derive(Debug, Clone)]
+enum Item {
+    /// Digits without leading zeros, compared by length and then lexically so any
+    /// number of digits works
+    Int(String),
+    Str(String),
+    List(Vec<Item>),
+}
+
+impl Item {
+    fn int(digits: &str) -> Item {
+        let trimmed = digits.trim_start_matches('0');
+        Item::Int(trimmed.to_string())
+    }
+
+    /// A string item; `a1`, `b2` and `m3` are short for alpha, beta and milestone.
+    fn string(value: &str, followed_by_digit: bool) -> Item {
+        let value = match value {
+            "a" if followed_by_digit => "alpha",
+            "b" if followed_by_digit => "beta",
+            "m" if followed_by_digit => "milestone",
+            other => other,
+        };
+        Item::Str(value.to_string())
+    }
+
+    fn parse(is_digit: bool, value: &str) -> Item {
+        if is_digit {
+            Item::int(value)
+        } else {
+            Item::string(value, false)
+        }
+    }
+
+    /// Whether this item is equivalent to nothing at all: `0`, `ga`, `final`, an empty list
+    fn is_null(&self) -> bool {
+        match self {
+            Item::Int(digits) => digits.is_empty(),
+            Item::Str(value) => qualifier_rank(value) == RELEASE_INDEX.to_string(),
+            Item::List(items) => items.is_empty(),
+        }
+    }
+}
+
+/// Sort key of a qualifier: its index in [`QUALIFIERS`], or `7-<qualifier>` when unknown.
+fn qualifier_rank(qualifier: &str) -> String {
+    let qualifier = match qualifier {
+        "ga" | "final" | "release" => "",
+        "cr" => "rc",
+        other => other,
+    };
+    match QUALIFIERS.iter().position(|q| *q == qualifier) {
+        Some(index) => index.to_string(),
+        None => format!("{}-{qualifier}", QUALIFIERS.len()),
+    }
+}
+
+fn compare_ints(a: &str, b: &str) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Compare two items, `None` standing for a missing item at the end of a shorter list.
+fn compare(a: Option<&Item>, b: Option<&Item>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(b)) => compare(Some(b), None).reverse(),
+        (Some(Item::Int(a)), None) => compare_ints(a, ""),
+        (Some(Item::Int(a)), Some(Item::Int(b))) => compare_ints(a, b),
+        (Some(Item::Int(_)), Some(_)) => Ordering::Greater,
+        (Some(Item::Str(a)), None) => qualifier_rank(a).cmp(&RELEASE_INDEX.to_string()),
+        (Some(Item::Str(_)), Some(Item::Int(_) | Item::List(_))) => Ordering::Less,
+        (Some(Item::Str(a)), Some(Item::Str(b))) => qualifier_rank(a).cmp(&qualifier_rank(b)),
+        (Some(Item::List(a)), None) => compare(a.first(), None),
+        (Some(Item::List(_)), Some(Item::Int(_))) => Ordering::Less,
+        (Some(Item::List(_)), Some(Item::Str(_))) => Ordering::Greater,
+        (Some(Item::List(a)), Some(Item::List(b))) => compare_lists(a, b),
+    }
+}
+
+fn compare_lists(a: &[Item], b: &[Item]) -> Ordering {
+    (0..a.len().max(b.len()))
+        .map(|i| compare(a.get(i), b.get(i)))
+        .find(|result| *result != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Drop trailing null items (`1.0.0` is `1`, `1.0-final` is `1`), looking past nested
+/// lists the way Maven does.
+fn normalize(items: &mut Vec<Item>) {
+    for i in (0..items.len()).rev() {
+        if items[i].is_null() {
+            items.remove(i);
+        } else if !matches!(items[i], Item::List(_)) {
+            break;
+        }
+    }
+}
+
+/// A version ordered the way Maven orders them, so `1.0-alpha-1 < 1.0-rc1 < 1.0 = 1.0.0.Final
+/// < 1.0-sp1 < 1.0.1` and `2.3.RELEASE = 2.3`. `.` separates numbers and `-` starts a
+/// qualifier; a switch between digits and letters counts as a `-`.
+#[derive(Debug, Clone)]
+pub struct ComparableVersion {
+    original: String,
+    items: Vec<Item>,
+}
+
+impl ComparableVersion {
+    pub fn new(version: &str) -> Self {
+        let lower = version.to_lowercase();
+        // Each `-` and digit/letter switch opens a nested list; `stack` holds the open ones
+        let mut stack: Vec<Vec<Item>> = vec![Vec::new()];
+        let mut is_digit = false;
+        let mut start = 0;
+
+        for (i, c) in lower.char_indices() {
+            let list = stack.last_mut().unwrap();
+            if c == '.' {
+                list.push(if i == start {
+                    Item::Int(String::new())
+                } else {
+                    Item::parse(is_digit, &lower[start..i])
+                });
+                start = i + 1;
+            } else if c == '-' {
+                list.push(if i == start {
+                    Item::Int(String::new())
+                } else {
+                    Item::parse(is_digit, &lower[start..i])
+                });
+                start = i + 1;
+                stack.push(Vec::new());
+            } else if c.is_ascii_digit() {
+                if !is_digit && i > start {
+                    list.push(Item::string(&lower[start..i], true));
+                    start = i;
+                    stack.push(Vec::new());
+                }
+                is_digit = true;
+            } else {
+                if is_digit && i > start {
+                    list.push(Item::parse(true, &lower[start..i]));
+                    start = i;
+                    stack.push(Vec::new());
+                }
+                is_digit = false;
+            }
+        }
+        if lower.len() > start {
+            let item = Item::parse(is_digit, &lower[start..]);
+            stack.last_mut().unwrap().push(item);
+        }
+
+        // Close the nested lists innermost first, each one ending its parent
+        let mut items = stack.pop().unwrap();
+        normalize(&mut items);
+        while let Some(mut parent) = stack.pop() {
+            parent.push(Item::List(items));
+            normalize(&mut parent);
+            items = parent;
+        }
+
+        ComparableVersion {
+            original: version.to_string(),
+            items,
+        }
+    }
+}
+
+impl fmt::Display for ComparableVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.original)
+    }
+}
+
+impl Ord for ComparableVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_lists(&self.items, &other.items)
+    }
+}
+
+impl PartialOrd for ComparableVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for ComparableVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ComparableVersion {}
+
+/// One interval of a range, e.g. `[1.2,2.0)`; a missing bound is unbounded.
+#[derive(Debug, Clone)]
+struct Restriction {
+    lower: Option<(ComparableVersion, bool)>,
+    upper: Option<(ComparableVersion, bool)>,
+}
+
+impl Restriction {
+    fn contains(&self, version: &ComparableVersion) -> bool {
+        let above = match &self.lower {
+            Some((lower, inclusive)) => version > lower || (*inclusive && version == lower),
+            None => true,
+        };
+        let below = match &self.upper {
+            Some((upper, inclusive)) => version < upper || (*inclusive && version == upper),
+            None => true,
+        };
+        above && below
+    }
+}
+
+/// A Maven version range such as `[1.2,2.0)`, `[3.0,)`, `[1.5]` or `(,1.0],[1.2,)`.
+#[derive(Debug, Clone)]
+pub struct VersionRange {
+    spec: String,
+    restrictions: Vec<Restriction>,
+}
+
+impl VersionRange {
+    /// Parse a range; `None` for plain versions and malformed ranges.
+    pub fn parse(spec: &str) -> Option<VersionRange> {
+        let mut rest = spec.trim();
+        if !is_range(rest) {
+            return None;
+        }
+        let mut restrictions = Vec::new();
+        while !rest.is_empty() {
+            let lower_inclusive = match rest.as_bytes()[0] {
+                b'[' => true,
+                b'(' => false,
+                _ => return None,
+            };
+            let end = rest.find([']', ')'])?;
+            let upper_inclusive = rest.as_bytes()[end] == b']';
+            let body = &rest[1..end];
+            let bound = |v: &str, inclusive| {
+                let v = v.trim();
+                (!v.is_empty()).then(|| (ComparableVersion::new(v), inclusive))
+            };
+            let restriction = match body.split_once(',') {
+                Some((lower, upper)) => Restriction {
+                    lower: bound(lower, lower_inclusive),
+                    upper: bound(upper, upper_inclusive),
+                },
+                // `[1.5]` pins exactly one version
+                None if lower_inclusive && upper_inclusive => Restriction {
+                    lower: bound(body, true),
+                    upper: bound(body, true),
+                },
+                None => return None,
+            };
+            restrictions.push(restriction);
+            rest = rest[end + 1..].trim_start_matches([',', ' ']);
+        }
+        Some(VersionRange {
+            spec: spec.to_string(),
+            restrictions,
+        })
+    }
+
+    pub fn contains(&self, version: &str) -> bool {
+        let version = ComparableVersion::new(version);
+        self.restrictions.iter().any(|r| r.contains(&version))
+    }
+
+    /// Highest of `versions` inside the range. SNAPSHOTs only qualify when a bound of the
+    /// range is itself a SNAPSHOT.
+    pub fn select<'a>(&self, versions: impl IntoIterator<Item = &'a String>) -> Option<&'a String> {
+        let allow_snapshots = self.spec.contains("-SNAPSHOT");
+        versions
+            .into_iter()
+            .filter(|v| allow_snapshots || !v.ends_with("-SNAPSHOT"))
+            .filter(|v| self.contains(v))
+            .max_by_key(|v| ComparableVersion::new(v))
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.spec)
+    }
+}
+
+/// Whether a requested version is a range rather than a single version.
+pub fn is_range(version: &str) -> bool {
+    version.starts_with(['[', '('])
+}
+
+/// Whether a version is a pre-release: a SNAPSHOT, or a qualifier such as alpha, beta,
+/// milestone or rc that sorts before the plain release of the same numbers
+/// (`2.0-rc1 < 2.0`). Platform qualifiers like `-jre` sort after it and are releases.
+pub fn is_prerelease(version: &str) -> bool {
+    let numbers = release_numbers(version);
+    !numbers.is_empty() && ComparableVersion::new(version) < ComparableVersion::new(numbers)
+}
+
+/// The leading dotted numbers of a version: `31.1` for `31.1-jre`.
+pub fn release_numbers(version: &str) -> &str {
+    let end = version
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(version.len());
+    version[..end].trim_end_matches('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(version: &str) -> ComparableVersion {
+        ComparableVersion::new(version)
+    }
+
+    fn versions(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn orders_qualifiers_like_maven() {
+        assert!(v("1.0-alpha-1") < v("1.0-rc1"));
+        assert!(v("1.0-rc1") < v("1.0"));
+        assert_eq!(v("1.0"), v("1.0.0.Final"));
+        assert!(v("1.0.0.Final") < v("1.0-sp1"));
+        assert!(v("1.0-sp1") < v("1.0.1"));
+    }
+
+    #[test]
+    fn release_qualifier_is_a_plain_release() {
+        assert_eq!(v("2.3.RELEASE"), v("2.3"));
+    }
+
+    #[test]
+    fn compares_numbers_numerically() {
+        assert!(v("1.10") > v("1.9"));
+    }
+
+    #[test]
+    fn multi_restriction_range() {
+        let range = VersionRange::parse("(,1.0],[1.2,)").unwrap();
+        assert!(range.contains("0.9"));
+        assert!(range.contains("1.0"));
+        assert!(!range.contains("1.1"));
+        assert!(range.contains("1.2"));
+        assert!(range.contains("3.0"));
+    }
+
+    #[test]
+    fn select_skips_snapshots() {
+        let range = VersionRange::parse("[1.0,2.0)").unwrap();
+        let available = versions(&["1.0", "1.5", "1.9-SNAPSHOT", "2.0"]);
+        assert_eq!(range.select(&available).map(String::as_str), Some("1.5"));
+
+        let snapshots = VersionRange::parse("[1.0,1.9-SNAPSHOT]").unwrap();
+        assert_eq!(
+            snapshots.select(&available).map(String::as_str),
+            Some("1.9-SNAPSHOT")
+        );
+    }
+
+    /// Maven's quirk, kept on purpose: an exclusive upper bound still admits that
+    /// version's pre-releases, since they sort below it.
+    #[test]
+    fn exclusive_upper_bound_admits_its_prereleases() {
+        let range = VersionRange::parse("[1.0,2.0)").unwrap();
+        assert!(range.contains("2.0-rc1"));
+        assert!(!range.contains("2.0"));
+    }
+}